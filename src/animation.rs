@@ -1,6 +1,10 @@
+use std::fmt;
+
+use serde::Deserialize;
+
 use crate::tetromino::{Rotation, Shape};
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Digit {
     Zero,
     One,
@@ -14,6 +18,27 @@ pub enum Digit {
     Nine,
 }
 
+impl Digit {
+    pub const ALL: [Digit; 10] = [
+        Digit::Zero,
+        Digit::One,
+        Digit::Two,
+        Digit::Three,
+        Digit::Four,
+        Digit::Five,
+        Digit::Six,
+        Digit::Seven,
+        Digit::Eight,
+        Digit::Nine,
+    ];
+}
+
+impl fmt::Display for Digit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", *self as u8)
+    }
+}
+
 impl From<usize> for Digit {
     fn from(value: usize) -> Self {
         match value {
@@ -43,7 +68,7 @@ impl From<u32> for Digit {
 
 pub type Animation = Vec<FallingTetromino>;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
 pub struct FallingTetromino {
     pub shape: Shape,
     pub rotation: Rotation,
@@ -59,135 +84,3 @@ impl FallingTetromino {
         }
     }
 }
-
-impl Into<Animation> for Digit {
-    fn into(self) -> Animation {
-        use Rotation::*;
-        use Shape::*;
-        match self {
-            Digit::Zero => vec![
-                FallingTetromino::new(J, Degrees90, 4),
-                FallingTetromino::new(I, Degrees90, 0),
-                FallingTetromino::new(S, NoRotation, 2),
-                FallingTetromino::new(T, Degrees270, 1),
-                FallingTetromino::new(T, Degrees90, 1),
-                FallingTetromino::new(Z, Degrees90, 4),
-                FallingTetromino::new(Z, Degrees90, 4),
-                FallingTetromino::new(T, Degrees270, 4),
-                FallingTetromino::new(Z, Degrees90, 0),
-                FallingTetromino::new(T, Degrees270, 0),
-                FallingTetromino::new(Z, NoRotation, 2),
-                FallingTetromino::new(J, NoRotation, 5),
-            ],
-            Digit::One => vec![
-                FallingTetromino::new(O, NoRotation, 4),
-                FallingTetromino::new(L, Degrees270, 4),
-                FallingTetromino::new(I, Degrees90, 5),
-                FallingTetromino::new(J, Degrees270, 4),
-                FallingTetromino::new(O, NoRotation, 4),
-            ],
-            Digit::Two => vec![
-                FallingTetromino::new(I, Degrees90, 0),
-                FallingTetromino::new(O, NoRotation, 4),
-                FallingTetromino::new(L, Degrees180, 1),
-                FallingTetromino::new(L, Degrees270, 1),
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(J, NoRotation, 5),
-                FallingTetromino::new(L, NoRotation, 0),
-                FallingTetromino::new(O, NoRotation, 4),
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(J, NoRotation, 5),
-                FallingTetromino::new(L, NoRotation, 0),
-            ],
-            Digit::Three => vec![
-                FallingTetromino::new(I, NoRotation, 2),
-                FallingTetromino::new(O, NoRotation, 0),
-                FallingTetromino::new(L, Degrees180, 2),
-                FallingTetromino::new(L, Degrees90, 5),
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(L, NoRotation, 0),
-                FallingTetromino::new(J, NoRotation, 5),
-                FallingTetromino::new(O, NoRotation, 4),
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(L, NoRotation, 0),
-                FallingTetromino::new(J, NoRotation, 5),
-            ],
-            Digit::Four => vec![
-                FallingTetromino::new(J, Degrees90, 4),
-                FallingTetromino::new(J, Degrees270, 4),
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(L, NoRotation, 0),
-                FallingTetromino::new(O, NoRotation, 0),
-                FallingTetromino::new(O, NoRotation, 0),
-                FallingTetromino::new(J, NoRotation, 5),
-                FallingTetromino::new(J, Degrees90, 4),
-                FallingTetromino::new(J, Degrees270, 4),
-            ],
-            Digit::Five => vec![
-                FallingTetromino::new(J, Degrees90, 4),
-                FallingTetromino::new(J, Degrees270, 4),
-                FallingTetromino::new(I, NoRotation, 0),
-                FallingTetromino::new(I, NoRotation, 0),
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(L, NoRotation, 0),
-                FallingTetromino::new(J, NoRotation, 5),
-                FallingTetromino::new(O, NoRotation, 0),
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(J, NoRotation, 5),
-                FallingTetromino::new(L, NoRotation, 0),
-            ],
-            Digit::Six => vec![
-                FallingTetromino::new(J, Degrees90, 4),
-                FallingTetromino::new(J, Degrees270, 4),
-                FallingTetromino::new(I, NoRotation, 0),
-                FallingTetromino::new(I, NoRotation, 0),
-                FallingTetromino::new(O, NoRotation, 0),
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(J, NoRotation, 5),
-                FallingTetromino::new(L, NoRotation, 0),
-                FallingTetromino::new(O, NoRotation, 0),
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(L, NoRotation, 0),
-                FallingTetromino::new(J, NoRotation, 5),
-            ],
-            Digit::Seven => vec![
-                FallingTetromino::new(O, NoRotation, 4),
-                FallingTetromino::new(L, Degrees270, 4),
-                FallingTetromino::new(I, Degrees90, 5),
-                FallingTetromino::new(J, Degrees270, 4),
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(L, NoRotation, 0),
-                FallingTetromino::new(J, NoRotation, 5),
-            ],
-            Digit::Eight => vec![
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(L, Degrees180, 2),
-                FallingTetromino::new(T, Degrees270, 0),
-                FallingTetromino::new(I, Degrees90, 5),
-                FallingTetromino::new(S, Degrees90, 1),
-                FallingTetromino::new(L, Degrees180, 1),
-                FallingTetromino::new(T, Degrees270, 4),
-                FallingTetromino::new(S, Degrees90, 5),
-                FallingTetromino::new(J, Degrees180, 0),
-                FallingTetromino::new(S, Degrees90, 1),
-                FallingTetromino::new(Z, NoRotation, 1),
-                FallingTetromino::new(Z, NoRotation, 3),
-                FallingTetromino::new(L, Degrees90, 5),
-            ],
-            Digit::Nine => vec![
-                FallingTetromino::new(O, NoRotation, 0),
-                FallingTetromino::new(L, Degrees180, 3),
-                FallingTetromino::new(L, NoRotation, 2),
-                FallingTetromino::new(O, NoRotation, 4),
-                FallingTetromino::new(I, Degrees90, 5),
-                FallingTetromino::new(I, NoRotation, 1),
-                FallingTetromino::new(T, Degrees270, 0),
-                FallingTetromino::new(L, Degrees180, 2),
-                FallingTetromino::new(Z, Degrees90, 4),
-                FallingTetromino::new(J, Degrees270, 1),
-                FallingTetromino::new(T, NoRotation, 3),
-                FallingTetromino::new(J, Degrees270, 0),
-            ],
-        }
-    }
-}
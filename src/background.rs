@@ -0,0 +1,184 @@
+use std::fs::File;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use pixel_loop::canvas::{Canvas, InMemoryCanvas};
+use pixel_loop::color::Color;
+
+/// What is shown behind the digits.
+pub enum Background {
+    Solid(Color),
+    /// Vertical gradient from the top to the bottom of the canvas
+    Gradient(Color, Color),
+    /// Image covering the whole canvas. The image is scaled to the size of
+    /// the canvas, keeping its aspect ratio and cutting off what does not fit.
+    Image {
+        image: InMemoryCanvas,
+        scaled: InMemoryCanvas,
+    },
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Solid(Color::from_rgb(0, 0, 0))
+    }
+}
+
+/// Parse a color given as hexadecimal RGB value, like `#1e90ff`. The leading
+/// `#` is optional.
+pub fn parse_color(color: &str) -> Result<Color> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+    };
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok(Color::from_rgb(r, g, b)),
+        _ => Err(anyhow!(
+            "'{}' is not a color like #1e90ff (red, green and blue in hexadecimal)",
+            color
+        )),
+    }
+}
+
+impl Background {
+    /// Parse the description of a background: a single color (`#102030`),
+    /// two colors for a gradient from top to bottom (`#102030:#000000`) or
+    /// the path to a PNG image.
+    pub fn parse(background: &str) -> Result<Self> {
+        if background.to_lowercase().ends_with(".png") {
+            return Self::load_image(Path::new(background));
+        }
+
+        match background.split_once(':') {
+            Some((top, bottom)) => Ok(Background::Gradient(
+                parse_color(top)?,
+                parse_color(bottom)?,
+            )),
+            None => Ok(Background::Solid(parse_color(background)?)),
+        }
+    }
+
+    pub fn load_image(path: &Path) -> Result<Self> {
+        let image = load_png(path)
+            .with_context(|| format!("Could not load background image {}", path.display()))?;
+        Ok(Background::Image {
+            scaled: InMemoryCanvas::new(1, 1, &Color::from_rgb(0, 0, 0)),
+            image,
+        })
+    }
+
+    /// Adapt the background to a new size of the canvas.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if let Background::Image { image, scaled } = self {
+            *scaled = cover(image, width, height);
+        }
+    }
+
+    /// Colors the tetrominos need to stand out against: both ends of a
+    /// gradient and the average color of an image.
+    pub fn colors(&self) -> Vec<Color> {
+        match self {
+            Background::Solid(color) => vec![*color],
+            Background::Gradient(top, bottom) => vec![*top, *bottom],
+            Background::Image { image, .. } => vec![*cover(image, 1, 1).get(0, 0)],
+        }
+    }
+
+    /// Fill the whole canvas with the background.
+    pub fn render<C: Canvas>(&self, canvas: &mut C) {
+        match self {
+            Background::Solid(color) => canvas.clear_screen(color),
+            Background::Gradient(top, bottom) => {
+                let height = canvas.height();
+                for y in 0..height {
+                    let t = y as f64 / (height.max(2) - 1) as f64;
+                    let mix = |top: u8, bottom: u8| {
+                        (top as f64 + (bottom as f64 - top as f64) * t).round() as u8
+                    };
+                    let color = Color::from_rgb(
+                        mix(top.r, bottom.r),
+                        mix(top.g, bottom.g),
+                        mix(top.b, bottom.b),
+                    );
+                    canvas.filled_rect(0, y as i64, canvas.width(), 1, &color);
+                }
+            }
+            Background::Image { scaled, .. } => canvas.blit(scaled, 0, 0, None),
+        }
+    }
+}
+
+/// Read a PNG image. Transparent parts of the image are shown black.
+fn load_png(path: &Path) -> Result<InMemoryCanvas> {
+    let mut decoder = png::Decoder::new(File::open(path)?);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+
+    let channels = info.color_type.samples();
+    let mut image = InMemoryCanvas::new(info.width, info.height, &Color::from_rgb(0, 0, 0));
+    for (i, pixel) in buffer[..info.buffer_size()]
+        .chunks_exact(channels)
+        .enumerate()
+    {
+        let (r, g, b, a) = match *pixel {
+            [gray] => (gray, gray, gray, 255),
+            [gray, a] => (gray, gray, gray, a),
+            [r, g, b] => (r, g, b, 255),
+            [r, g, b, a] => (r, g, b, a),
+            _ => unreachable!("PNG pixels have one to four channels"),
+        };
+        let alpha = |c: u8| (c as u32 * a as u32 / 255) as u8;
+        image.set(
+            i as u32 % info.width,
+            i as u32 / info.width,
+            &Color::from_rgb(alpha(r), alpha(g), alpha(b)),
+        );
+    }
+
+    Ok(image)
+}
+
+/// Scale the image to cover the given size, cutting off the parts of the
+/// image on both sides, which do not fit. Every pixel of the result is the
+/// average of the pixels of the image it covers.
+fn cover(image: &InMemoryCanvas, width: u32, height: u32) -> InMemoryCanvas {
+    let mut scaled = InMemoryCanvas::new(width.max(1), height.max(1), &Color::from_rgb(0, 0, 0));
+
+    let factor = (width as f64 / image.width() as f64).max(height as f64 / image.height() as f64);
+    let x_start = (image.width() as f64 - width as f64 / factor) / 2.0;
+    let y_start = (image.height() as f64 - height as f64 / factor) / 2.0;
+    // Range of source pixels covered by a pixel of the result
+    let range = |start: f64, i: u32, size: u32| {
+        let from = (start + i as f64 / factor) as u32;
+        let to = ((start + (i + 1) as f64 / factor) as u32).clamp(from + 1, size);
+        from.min(size - 1)..to
+    };
+
+    for y in 0..height {
+        let ys = range(y_start, y, image.height());
+        for x in 0..width {
+            let xs = range(x_start, x, image.width());
+            let (mut r, mut g, mut b, mut count) = (0u32, 0u32, 0u32, 0u32);
+            for source_y in ys.clone() {
+                for source_x in xs.clone() {
+                    let color = image.get(source_x, source_y);
+                    r += color.r as u32;
+                    g += color.g as u32;
+                    b += color.b as u32;
+                    count += 1;
+                }
+            }
+            let count = count.max(1);
+            scaled.set(
+                x,
+                y,
+                &Color::from_rgb((r / count) as u8, (g / count) as u8, (b / count) as u8),
+            );
+        }
+    }
+
+    scaled
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use anyhow::{anyhow, Error, Result};
+use serde::Deserialize;
+
+/// A monochrome image of a glyph, describing which cells should be covered by
+/// tetrominos.
+///
+/// In its textual form every line is one row of the bitmap, with `#` marking a
+/// filled cell and `.` marking an empty one:
+///
+/// ```text
+/// ######
+/// ##..##
+/// ######
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Bitmap {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+}
+
+impl Bitmap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![false; width * height],
+        }
+    }
+
+    pub fn parse<T: AsRef<str>>(text: T) -> Result<Self> {
+        let rows: Vec<&str> = text
+            .as_ref()
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        let width = rows.first().map(|row| row.chars().count()).unwrap_or(0);
+        let mut bitmap = Self::new(width, rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(anyhow!(
+                    "Row {} of bitmap has a width of {}, expected {}",
+                    y + 1,
+                    row.chars().count(),
+                    width
+                ));
+            }
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '#' => bitmap.set(x as i64, y as i64, true),
+                    '.' => {}
+                    _ => {
+                        return Err(anyhow!(
+                            "Invalid character '{}' in row {} of bitmap (use '#' and '.')",
+                            c,
+                            y + 1
+                        ))
+                    }
+                }
+            }
+        }
+
+        Ok(bitmap)
+    }
+
+    pub fn filled(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![true; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64
+    }
+
+    /// Whether the cell at the given position is filled. Cells outside of the
+    /// bitmap are always empty.
+    pub fn get(&self, x: i64, y: i64) -> bool {
+        self.contains(x, y) && self.cells[y as usize * self.width + x as usize]
+    }
+
+    pub fn set(&mut self, x: i64, y: i64, filled: bool) {
+        if self.contains(x, y) {
+            self.cells[y as usize * self.width + x as usize] = filled;
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.cells.iter().filter(|&&filled| filled).count()
+    }
+}
+
+impl fmt::Display for Bitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                write!(
+                    f,
+                    "{}",
+                    if self.get(x as i64, y as i64) {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Bitmap {
+    type Error = Error;
+
+    fn try_from(text: String) -> Result<Self> {
+        Self::parse(text)
+    }
+}
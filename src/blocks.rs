@@ -0,0 +1,226 @@
+use clap::ValueEnum;
+use pixel_loop::canvas::Canvas;
+use pixel_loop::color::Color;
+
+use crate::scene::{Cell, Scene};
+use crate::tetromino::dim;
+
+/// Brightness of the darker edges of a block
+const SHADOW: f64 = 0.55;
+/// How far the lighter edges of a block are brightened towards white
+const HIGHLIGHT: f64 = 0.45;
+
+/// Look of every single cell of a tetromino, once cells are larger than one
+/// pixel (see `--scale`).
+#[derive(Debug, Copy, Clone, PartialEq, Default, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum BlockStyle {
+    /// Plainly filled cells (the classic look)
+    #[default]
+    Flat,
+    /// Tetrominos with lighter upper and left edges and darker lower and right edges
+    Bevel,
+    /// Tetrominos with a darker border and a small gap between them
+    Outline,
+    /// Only the border of every tetromino, leaving it hollow
+    Ghost,
+}
+
+/// Brighten the color towards white (1.0) or keep it as it is (0.0).
+fn lighten(color: &Color, amount: f64) -> Color {
+    let channel = |c: u8| c + ((255 - c) as f64 * amount) as u8;
+    Color::from_rgb(channel(color.r), channel(color.g), channel(color.b))
+}
+
+/// Sides of a cell, at which the tetromino it belongs to ends. Edges of
+/// block styles are only drawn there, so every tetromino is outlined as a
+/// whole.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Edges {
+    pub top: bool,
+    pub left: bool,
+    pub bottom: bool,
+    pub right: bool,
+}
+
+impl Edges {
+    /// Edges of a cell on its own
+    const ALL: Edges = Edges {
+        top: true,
+        left: true,
+        bottom: true,
+        right: true,
+    };
+
+    /// Edges of the cell at the given position of the scene. Cells of
+    /// anything but a tetromino are treated as blocks on their own.
+    fn of(scene: &Scene, x: i64, y: i64) -> Self {
+        let Cell::Piece(piece) = scene.cell(x, y) else {
+            return Self::ALL;
+        };
+        let ends = |x: i64, y: i64| match scene.cell(x, y) {
+            Cell::Piece(other) => other.id != piece.id,
+            _ => true,
+        };
+        Self {
+            top: ends(x, y - 1),
+            left: ends(x - 1, y),
+            bottom: ends(x, y + 1),
+            right: ends(x + 1, y),
+        }
+    }
+}
+
+/// Draw the given edges of a rectangle with the given upper left corner.
+fn border<C: Canvas>(
+    canvas: &mut C,
+    (x, y): (i64, i64),
+    (width, height): (u32, u32),
+    edges: Edges,
+    color: &Color,
+) {
+    if edges.top {
+        canvas.filled_rect(x, y, width, 1, color);
+    }
+    if edges.left {
+        canvas.filled_rect(x, y, 1, height, color);
+    }
+    if edges.bottom {
+        canvas.filled_rect(x, y + height as i64 - 1, width, 1, color);
+    }
+    if edges.right {
+        canvas.filled_rect(x + width as i64 - 1, y, 1, height, color);
+    }
+}
+
+impl BlockStyle {
+    /// Draw a single cell as square of the given size with its upper left
+    /// corner at the given position, with edges at the given sides. Cells of
+    /// a single pixel are always drawn flat.
+    pub fn draw_cell<C: Canvas>(
+        &self,
+        canvas: &mut C,
+        (x, y): (i64, i64),
+        size: u32,
+        edges: Edges,
+        color: &Color,
+    ) {
+        if size < 2 {
+            canvas.filled_rect(x, y, size, size, color);
+            return;
+        }
+
+        match self {
+            BlockStyle::Flat => canvas.filled_rect(x, y, size, size, color),
+            BlockStyle::Bevel => {
+                canvas.filled_rect(x, y, size, size, color);
+                let highlight = Edges {
+                    bottom: false,
+                    right: false,
+                    ..edges
+                };
+                border(
+                    canvas,
+                    (x, y),
+                    (size, size),
+                    highlight,
+                    &lighten(color, HIGHLIGHT),
+                );
+                // The shadow leaves out the corners of the highlight.
+                let shadow = Edges {
+                    top: false,
+                    left: false,
+                    ..edges
+                };
+                border(
+                    canvas,
+                    (x + 1, y + 1),
+                    (size - 1, size - 1),
+                    shadow,
+                    &dim(color, SHADOW),
+                );
+            }
+            BlockStyle::Outline => {
+                // A gap of one pixel is left to the tetrominos on the right
+                // and below.
+                let width = size - edges.right as u32;
+                let height = size - edges.bottom as u32;
+                canvas.filled_rect(x, y, width, height, color);
+                if size > 3 {
+                    border(canvas, (x, y), (width, height), edges, &dim(color, SHADOW));
+                }
+            }
+            BlockStyle::Ghost => border(canvas, (x, y), (size, size), edges, color),
+        }
+    }
+}
+
+/// Draw the scene onto the canvas, turning every cell of the scene into a
+/// square of `scale` x `scale` pixels in the given style. Empty cells are
+/// left out.
+pub fn render_scaled<C: Canvas>(scene: &Scene, canvas: &mut C, scale: u32, style: BlockStyle) {
+    for y in 0..scene.height() as i64 {
+        for x in 0..scene.width() as i64 {
+            if scene.cell(x, y) == Cell::Empty {
+                continue;
+            }
+            let color = scene.get(x as u32, y as u32);
+            let position = (x * scale as i64, y * scale as i64);
+            style.draw_cell(canvas, position, scale, Edges::of(scene, x, y), color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::CellCanvas;
+    use crate::tetromino::Shape;
+    use pixel_loop::canvas::InMemoryCanvas;
+
+    #[test]
+    fn edges_separate_tetrominos_of_the_same_color() {
+        let color = Color::from_rgb(255, 0, 0);
+        let mut scene = Scene::new(8, 4);
+        scene.set_piece(Some(Shape::O));
+        scene.filled_rect(0, 0, 2, 2, &color);
+        scene.set_piece(Some(Shape::O));
+        scene.filled_rect(2, 0, 2, 2, &color);
+        scene.set_piece(None);
+
+        let edges = Edges::of(&scene, 1, 0);
+        assert_eq!(
+            edges,
+            Edges {
+                top: true,
+                left: false,
+                bottom: false,
+                right: true
+            }
+        );
+        let edges = Edges::of(&scene, 2, 1);
+        assert_eq!(
+            edges,
+            Edges {
+                top: false,
+                left: true,
+                bottom: true,
+                right: false
+            }
+        );
+    }
+
+    #[test]
+    fn black_tetrominos_are_not_empty() {
+        let black = Color::from_rgb(0, 0, 0);
+        let white = Color::from_rgb(255, 255, 255);
+        let mut scene = Scene::new(4, 4);
+        scene.set_piece(Some(Shape::O));
+        scene.filled_rect(0, 0, 2, 2, &black);
+
+        let mut canvas = InMemoryCanvas::new(8, 8, &white);
+        render_scaled(&scene, &mut canvas, 2, BlockStyle::Flat);
+        assert_eq!(*canvas.get(3, 3), black);
+        assert_eq!(*canvas.get(5, 5), white);
+    }
+}
@@ -0,0 +1,135 @@
+use pixel_loop::color::Color;
+
+use crate::animation::{Digit, FallingTetromino};
+use crate::scene::CellCanvas;
+use crate::tetromino::Board;
+use crate::text::{draw_text, text_width, CHAR_HEIGHT, CHAR_WIDTH};
+
+/// Vertical distance between two lines of text
+const LINE_HEIGHT: i64 = CHAR_HEIGHT + 2;
+
+/// Width of the panels on both sides of the well, fitting five characters
+const PANEL_WIDTH: i64 = 5 * (CHAR_WIDTH + 1) - 1;
+
+/// Horizontal space between a wall of the well and the panel next to it
+const PANEL_SPACING: i64 = 2;
+
+/// Inner size of the box showing the next tetromino
+const NEXT_BOX_SIZE: i64 = 6;
+
+/// Width needed by the walls and panels around the digits
+pub const WIDTH: i64 = 2 * (1 + PANEL_SPACING + PANEL_WIDTH);
+
+/// Decoration of the clock as a game of Tetris: the digits are placed in a
+/// well, with the next tetromino on one side and the score, level and lines
+/// on the other side. The values are taken from the shown time.
+///
+/// The walls of the well are right next to the digits and its floor is right
+/// below them.
+pub struct Chrome {
+    /// Column of the left wall
+    left: i64,
+    /// Column of the right wall
+    right: i64,
+    y_stop: i64,
+    canvas_width: i64,
+}
+
+impl Chrome {
+    pub fn new(x_start: i64, layout_width: i64, y_stop: i64, canvas_width: u32) -> Self {
+        Self {
+            left: x_start - 1,
+            right: x_start + layout_width,
+            y_stop,
+            canvas_width: canvas_width as i64,
+        }
+    }
+
+    /// Draw the chrome for the given digits of the time, showing the next
+    /// tetromino of the digit currently assembled. Panels are left out, if
+    /// they do not fit into the canvas.
+    pub fn render<C: CellCanvas>(
+        &self,
+        canvas: &mut C,
+        digits: &[Digit],
+        next: Option<(FallingTetromino, Color)>,
+    ) {
+        let wall = Color::from_rgb(80, 80, 80);
+        let text = Color::from_rgb(200, 200, 200);
+
+        let wall_height = self.y_stop as u32 + 1;
+        canvas.filled_rect(self.left, 0, 1, wall_height, &wall);
+        canvas.filled_rect(self.right, 0, 1, wall_height, &wall);
+        canvas.filled_rect(
+            self.left,
+            self.y_stop,
+            (self.right - self.left + 1) as u32,
+            1,
+            &wall,
+        );
+
+        let number = |i: usize| digits[i] as i64 * 10 + digits[i + 1] as i64;
+        let (hours, minutes, seconds) = (number(0), number(2), number(4));
+        let lines = [
+            "SCORE".to_string(),
+            (hours * 3600 + minutes * 60 + seconds).to_string(),
+            "LEVEL".to_string(),
+            hours.to_string(),
+            "LINES".to_string(),
+            minutes.to_string(),
+        ];
+
+        // Both panels end at the floor of the well, unless the canvas is not
+        // high enough for all lines of the score.
+        let x = self.left - PANEL_SPACING - PANEL_WIDTH;
+        if x >= 0 {
+            let y = (self.y_stop + 1 - lines.len() as i64 * LINE_HEIGHT).max(1);
+            for (i, line) in lines.iter().enumerate() {
+                let color = if i % 2 == 0 { &wall } else { &text };
+                let line_x = x + PANEL_WIDTH - text_width(line);
+                draw_text(canvas, line_x, y + i as i64 * LINE_HEIGHT, line, color);
+            }
+        }
+
+        // The last column of the canvas is never shown.
+        let x = self.right + 1 + PANEL_SPACING;
+        if x + PANEL_WIDTH < self.canvas_width {
+            let box_x = x + (PANEL_WIDTH - NEXT_BOX_SIZE - 2) / 2;
+            let box_y = self.y_stop - NEXT_BOX_SIZE - 1;
+            draw_text(canvas, x, box_y - LINE_HEIGHT, "NEXT", &wall);
+            let size = NEXT_BOX_SIZE as u32 + 2;
+            canvas.filled_rect(box_x, box_y, size, 1, &wall);
+            canvas.filled_rect(box_x, box_y + size as i64 - 1, size, 1, &wall);
+            canvas.filled_rect(box_x, box_y, 1, size, &wall);
+            canvas.filled_rect(box_x + size as i64 - 1, box_y, 1, size, &wall);
+
+            if let Some((tetromino, color)) = next {
+                render_centered(canvas, box_x + 1, box_y + 1, tetromino, color);
+            }
+        }
+    }
+}
+
+/// Draw the tetromino in the middle of the box with the given upper left
+/// corner.
+fn render_centered<C: CellCanvas>(
+    canvas: &mut C,
+    x: i64,
+    y: i64,
+    FallingTetromino {
+        shape, rotation, ..
+    }: FallingTetromino,
+    color: Color,
+) {
+    let cells = shape.cells(rotation);
+    let min_x = cells.iter().map(|(x, _)| *x).min().unwrap_or(0);
+    let max_x = cells.iter().map(|(x, _)| *x).max().unwrap_or(0);
+    let min_y = cells.iter().map(|(_, y)| *y).min().unwrap_or(0);
+    let max_y = cells.iter().map(|(_, y)| *y).max().unwrap_or(0);
+
+    let dx = (NEXT_BOX_SIZE - (max_x - min_x + 1)) / 2 - min_x;
+    let dy = (NEXT_BOX_SIZE - (max_y - min_y + 1)) / 2 - min_y;
+    let mut board = Board::new(x, y, y);
+    board.place_tetromino(dx, dy, color, shape, rotation);
+    board.render(canvas);
+}
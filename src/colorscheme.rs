@@ -0,0 +1,470 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use pixel_loop::color::Color;
+use serde::Deserialize;
+
+use crate::animation::Digit;
+use crate::background::{parse_color, Background};
+use crate::schedule;
+use crate::tetromino::{dim, Shape};
+
+/// Brightness of the night colorscheme compared to the original colors
+const NIGHT_BRIGHTNESS: f64 = 0.45;
+
+/// Colorscheme selected by its name, either one of the built-in ones or one
+/// defined in the config file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Colorscheme {
+    Builtin(BuiltinColorscheme),
+    Custom(Rc<CustomColorscheme>),
+}
+
+impl Default for Colorscheme {
+    fn default() -> Self {
+        Colorscheme::Builtin(BuiltinColorscheme::default())
+    }
+}
+
+impl Colorscheme {
+    /// Look up the colorscheme with the given name.
+    pub fn find(name: &str, custom: &BTreeMap<String, ColorschemeConfig>) -> Result<Self> {
+        if let Ok(builtin) = BuiltinColorscheme::from_str(name, true) {
+            return Ok(Colorscheme::Builtin(builtin));
+        }
+
+        match custom.get(name) {
+            Some(config) => Ok(Colorscheme::Custom(Rc::new(CustomColorscheme::new(
+                name, config,
+            )?))),
+            None => Err(anyhow!(
+                "Unknown colorscheme '{}', available are: {}",
+                name,
+                list(custom)
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    pub fn apply(&self, shape: Shape, digit: Digit, position: usize) -> Color {
+        match self {
+            Colorscheme::Builtin(builtin) => builtin.apply(shape, digit, position),
+            Colorscheme::Custom(custom) => custom.apply(shape, digit, position),
+        }
+    }
+
+    /// Colors of the colorscheme named by what they belong to: the shapes,
+    /// the positions or the digits, depending on what the colorscheme
+    /// colors tetrominos by.
+    pub fn named_colors(&self) -> Vec<(String, Color)> {
+        let varies = |colors: &[Color]| colors.iter().any(|&color| color != colors[0]);
+
+        let shapes: Vec<Color> = Shape::ALL
+            .iter()
+            .map(|&shape| self.apply(shape, Digit::Zero, 0))
+            .collect();
+        if varies(&shapes) {
+            return Shape::ALL
+                .iter()
+                .map(|shape| format!("{:?}", shape))
+                .zip(shapes)
+                .collect();
+        }
+
+        let positions: Vec<Color> = (0..6)
+            .map(|position| self.apply(Shape::O, Digit::Zero, position))
+            .collect();
+        if varies(&positions) {
+            return (1..=6)
+                .map(|position| format!("Position {}", position))
+                .zip(positions)
+                .collect();
+        }
+
+        Digit::ALL
+            .iter()
+            .enumerate()
+            .map(|(number, &digit)| {
+                let color = self.apply(Shape::O, digit, 0);
+                (format!("Digit {}", number), color)
+            })
+            .collect()
+    }
+
+    /// All distinct colors of the colorscheme in the order of the shapes,
+    /// positions or digits they belong to.
+    pub fn palette(&self) -> Vec<Color> {
+        let mut palette = vec![];
+        for (_, color) in self.named_colors() {
+            if !palette.contains(&color) {
+                palette.push(color);
+            }
+        }
+        palette
+    }
+
+    /// Background shown behind the digits, unless another one is chosen.
+    pub fn background(&self) -> Result<Background> {
+        match self {
+            Colorscheme::Builtin(_) => Ok(Background::default()),
+            Colorscheme::Custom(custom) => custom
+                .background
+                .as_deref()
+                .map_or(Ok(Background::default()), Background::parse),
+        }
+    }
+}
+
+/// Names and descriptions of all colorschemes, the built-in ones first.
+pub fn list(custom: &BTreeMap<String, ColorschemeConfig>) -> Vec<(String, String)> {
+    let builtin = BuiltinColorscheme::value_variants()
+        .iter()
+        .filter_map(|builtin| builtin.to_possible_value())
+        .map(|value| {
+            let help = value.get_help().map(|help| help.to_string());
+            (value.get_name().to_string(), help.unwrap_or_default())
+        });
+    let auto = std::iter::once((
+        schedule::AUTO.to_string(),
+        "Changing by the date and time of the day".to_string(),
+    ));
+    let custom = custom.iter().map(|(name, config)| {
+        let description = config.description.clone();
+        (name.clone(), description.unwrap_or_default())
+    });
+    builtin.chain(auto).chain(custom).collect()
+}
+
+/// Definition of a colorscheme in the config file. Tetrominos are colored
+/// either by their shape, by the digit they belong to or by the position of
+/// that digit. Colors are given like `#1e90ff`.
+///
+/// ```toml
+/// [colorschemes.sunset]
+/// description = "Warm colors of the evening sky"
+/// background = "#2a0a1a:#000000"
+/// shapes = { L = "#ff5e5b", J = "#d72638", O = "#ffd166", T = "#f49d37", I = "#ff9f1c", S = "#f08a4b", Z = "#c9184a" }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColorschemeConfig {
+    pub description: Option<String>,
+    /// Same as `--background`
+    pub background: Option<String>,
+    /// Color of every shape
+    pub shapes: Option<BTreeMap<Shape, String>>,
+    /// Colors of the digits 0 to 9
+    pub digits: Option<Vec<String>>,
+    /// Colors of the six digit positions from left to right
+    pub positions: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Colors {
+    /// Colors in the order of `Shape::ALL`
+    Shapes(Vec<Color>),
+    Digits(Vec<Color>),
+    Positions(Vec<Color>),
+}
+
+/// Colorscheme defined in the config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomColorscheme {
+    colors: Colors,
+    background: Option<String>,
+}
+
+impl CustomColorscheme {
+    pub fn new(name: &str, config: &ColorschemeConfig) -> Result<Self> {
+        Self::validated(config).with_context(|| format!("Invalid colorscheme '{}'", name))
+    }
+
+    /// Colorscheme with a color for every shape, in the order of
+    /// `Shape::ALL`, and a background like `--background`.
+    pub fn from_shapes(colors: [Color; 7], background: Option<String>) -> Self {
+        Self {
+            colors: Colors::Shapes(colors.to_vec()),
+            background,
+        }
+    }
+
+    fn validated(config: &ColorschemeConfig) -> Result<Self> {
+        let colors = |colors: &[String], what: &str, count: usize| -> Result<Vec<Color>> {
+            if colors.len() != count {
+                return Err(anyhow!(
+                    "{} colors are needed for the {}, but {} are given",
+                    count,
+                    what,
+                    colors.len()
+                ));
+            }
+            colors.iter().map(|color| parse_color(color)).collect()
+        };
+
+        let colors = match (&config.shapes, &config.digits, &config.positions) {
+            (Some(shapes), None, None) => Colors::Shapes(
+                Shape::ALL
+                    .iter()
+                    .map(|shape| {
+                        let color = shapes
+                            .get(shape)
+                            .ok_or_else(|| anyhow!("No color for the shape {:?}", shape))?;
+                        parse_color(color).with_context(|| format!("Shape {:?}", shape))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            (None, Some(digits), None) => Colors::Digits(colors(digits, "digits", 10)?),
+            (None, None, Some(positions)) => Colors::Positions(colors(positions, "positions", 6)?),
+            _ => {
+                return Err(anyhow!(
+                    "Exactly one of shapes, digits or positions needs to be given"
+                ))
+            }
+        };
+
+        // Images are only loaded, once the background is shown.
+        if let Some(background) = &config.background {
+            if !background.to_lowercase().ends_with(".png") {
+                Background::parse(background).context("Background")?;
+            }
+        }
+
+        Ok(Self {
+            colors,
+            background: config.background.clone(),
+        })
+    }
+
+    pub fn apply(&self, shape: Shape, digit: Digit, position: usize) -> Color {
+        match &self.colors {
+            Colors::Shapes(colors) => {
+                let index = Shape::ALL.iter().position(|&other| other == shape);
+                colors[index.expect("every shape is part of Shape::ALL")]
+            }
+            Colors::Digits(colors) => colors[digit as usize],
+            Colors::Positions(colors) => colors[position % colors.len()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum BuiltinColorscheme {
+    #[default]
+    #[value(help = "Classic Tetris colors with bright, distinct shades")]
+    Original,
+    #[value(help = "Different shades of gray for a monochrome look")]
+    Grayscale,
+    #[value(help = "Colors based on the digit position in the time display")]
+    Position,
+    #[value(help = "Each digit (0-9) gets its own distinct color")]
+    Digit,
+    #[value(help = "Vibrant neon colors inspired by cyberpunk aesthetics")]
+    Neon,
+    #[value(help = "Soft, muted pastel colors for a gentle appearance")]
+    Pastel,
+    #[value(help = "Shades of blue and turquoise inspired by ocean depths")]
+    Ocean,
+    #[value(help = "Warm fall colors with reds, browns and golden tones")]
+    Autumn,
+    #[value(help = "Traditional Christmas theme with reds, greens and gold")]
+    Christmas,
+    #[value(help = "Warm spectrum focusing on yellows and oranges")]
+    Warm,
+    #[value(help = "Digital green shades inspired by The Matrix movie")]
+    Matrix,
+    #[value(help = "Rich and deep purple/violet color palette")]
+    Purple,
+    #[value(help = "Dimmed classic colors for dark rooms at night")]
+    Night,
+    #[value(help = "Colors distinguishable without seeing green (deuteranopia)")]
+    Deuteranopia,
+    #[value(help = "Colors distinguishable without seeing red (protanopia)")]
+    Protanopia,
+    #[value(help = "Colors distinguishable without seeing blue (tritanopia)")]
+    Tritanopia,
+    #[value(
+        name = "high-contrast",
+        help = "Bright colors standing out clearly against the background"
+    )]
+    HighContrast,
+}
+
+impl BuiltinColorscheme {
+    pub fn apply(&self, shape: Shape, digit: Digit, position: usize) -> Color {
+        match self {
+            BuiltinColorscheme::Original => match shape {
+                Shape::L => Color::from_rgb(223, 155, 42),
+                Shape::J => Color::from_rgb(22, 0, 230),
+                Shape::O => Color::from_rgb(237, 238, 57),
+                Shape::T => Color::from_rgb(136, 26, 231),
+                Shape::I => Color::from_rgb(103, 232, 236),
+                Shape::S => Color::from_rgb(100, 233, 49),
+                Shape::Z => Color::from_rgb(213, 50, 27),
+            },
+            BuiltinColorscheme::Grayscale => match shape {
+                Shape::L => Color::from_rgb(178, 178, 178),
+                Shape::J => Color::from_rgb(74, 74, 74),
+                Shape::O => Color::from_rgb(234, 234, 234),
+                Shape::T => Color::from_rgb(98, 98, 98),
+                Shape::I => Color::from_rgb(216, 216, 216),
+                Shape::S => Color::from_rgb(200, 200, 200),
+                Shape::Z => Color::from_rgb(120, 120, 120),
+            },
+            BuiltinColorscheme::Position => match position {
+                0 => Color::from_rgb(237, 238, 57),
+                1 => Color::from_rgb(213, 50, 27),
+                2 => Color::from_rgb(223, 155, 42),
+                3 => Color::from_rgb(136, 26, 231),
+                4 => Color::from_rgb(103, 232, 236),
+                5 => Color::from_rgb(100, 233, 49),
+                6 => Color::from_rgb(213, 50, 27),
+                _ => panic!("Unknown position for Colorscheme 'position' {}", position),
+            },
+            BuiltinColorscheme::Digit => match digit {
+                Digit::Zero => Color::from_rgb(180, 180, 180),
+                Digit::One => Color::from_rgb(255, 215, 0),
+                Digit::Two => Color::from_rgb(255, 105, 180),
+                Digit::Three => Color::from_rgb(0, 139, 139),
+                Digit::Four => Color::from_rgb(255, 160, 122),
+                Digit::Five => Color::from_rgb(147, 112, 219),
+                Digit::Six => Color::from_rgb(32, 178, 170),
+                Digit::Seven => Color::from_rgb(255, 218, 185),
+                Digit::Eight => Color::from_rgb(176, 196, 222),
+                Digit::Nine => Color::from_rgb(255, 192, 203),
+            },
+            BuiltinColorscheme::Neon => match shape {
+                // Bright, glowing neon colors
+                Shape::L => Color::from_rgb(255, 41, 117), // Hot pink
+                Shape::J => Color::from_rgb(0, 255, 255),  // Cyan
+                Shape::O => Color::from_rgb(255, 236, 39), // Neon yellow
+                Shape::T => Color::from_rgb(167, 0, 255),  // Bright purple
+                Shape::I => Color::from_rgb(0, 255, 169),  // Bright mint
+                Shape::S => Color::from_rgb(123, 255, 0),  // Lime green
+                Shape::Z => Color::from_rgb(255, 82, 0),   // Neon orange
+            },
+            BuiltinColorscheme::Pastel => match shape {
+                // Soft, muted pastel colors
+                Shape::L => Color::from_rgb(255, 183, 197), // Soft pink
+                Shape::J => Color::from_rgb(174, 198, 255), // Pastel blue
+                Shape::O => Color::from_rgb(255, 251, 150), // Pastel yellow
+                Shape::T => Color::from_rgb(211, 178, 255), // Pastel purple
+                Shape::I => Color::from_rgb(178, 255, 241), // Pastel turquoise
+                Shape::S => Color::from_rgb(178, 255, 178), // Pastel green
+                Shape::Z => Color::from_rgb(255, 178, 178), // Pastel red
+            },
+            BuiltinColorscheme::Ocean => match shape {
+                // Colors from shallow to deep ocean
+                Shape::L => Color::from_rgb(144, 224, 239), // Light aqua
+                Shape::J => Color::from_rgb(0, 119, 182),   // Deep blue
+                Shape::O => Color::from_rgb(202, 240, 248), // Sea foam
+                Shape::T => Color::from_rgb(3, 4, 94),      // Deep ocean
+                Shape::I => Color::from_rgb(0, 150, 199),   // Azure
+                Shape::S => Color::from_rgb(72, 202, 228),  // Sky blue
+                Shape::Z => Color::from_rgb(0, 180, 216),   // Turquoise
+            },
+            BuiltinColorscheme::Autumn => match shape {
+                // Warm fall colors
+                Shape::L => Color::from_rgb(230, 125, 35), // Burnt orange
+                Shape::J => Color::from_rgb(153, 62, 29),  // Auburn
+                Shape::O => Color::from_rgb(255, 190, 15), // Golden yellow
+                Shape::T => Color::from_rgb(130, 48, 56),  // Wine red
+                Shape::I => Color::from_rgb(217, 80, 48),  // Rust
+                Shape::S => Color::from_rgb(85, 107, 47),  // Olive green
+                Shape::Z => Color::from_rgb(168, 50, 27),  // Maroon
+            },
+            BuiltinColorscheme::Christmas => match shape {
+                // Traditional Christmas colors with rich reds, greens, and gold accents
+                Shape::L => Color::from_rgb(176, 0, 0), // Deep Christmas red
+                Shape::J => Color::from_rgb(0, 105, 0), // Forest green
+                Shape::O => Color::from_rgb(255, 215, 0), // Golden yellow
+                Shape::T => Color::from_rgb(146, 22, 34), // Cardinal red
+                Shape::I => Color::from_rgb(0, 140, 0), // Christmas tree green
+                Shape::S => Color::from_rgb(212, 175, 55), // Metallic gold
+                Shape::Z => Color::from_rgb(190, 0, 0), // Bright Christmas red
+            },
+            BuiltinColorscheme::Warm => match shape {
+                // Warm colors focusing on yellows and oranges
+                Shape::L => Color::from_rgb(255, 167, 0), // Pure orange
+                Shape::J => Color::from_rgb(255, 140, 0), // Dark orange
+                Shape::O => Color::from_rgb(255, 215, 0), // Golden yellow
+                Shape::T => Color::from_rgb(255, 198, 0), // Amber
+                Shape::I => Color::from_rgb(255, 179, 25), // Marigold
+                Shape::S => Color::from_rgb(255, 126, 0), // Safety orange
+                Shape::Z => Color::from_rgb(255, 103, 0), // Burnt orange
+            },
+            BuiltinColorscheme::Matrix => match shape {
+                // Different intensities of the iconic Matrix green
+                Shape::L => Color::from_rgb(0, 255, 0), // Pure Matrix green
+                Shape::J => Color::from_rgb(0, 185, 0), // Medium bright green
+                Shape::O => Color::from_rgb(172, 255, 172), // Light digital green
+                Shape::T => Color::from_rgb(0, 140, 0), // Dark digital green
+                Shape::I => Color::from_rgb(0, 215, 0), // Bright digital green
+                Shape::S => Color::from_rgb(128, 255, 128), // Pale Matrix green
+                Shape::Z => Color::from_rgb(0, 155, 0), // Muted Matrix green
+            },
+            BuiltinColorscheme::Night => dim(
+                &BuiltinColorscheme::Original.apply(shape, digit, position),
+                NIGHT_BRIGHTNESS,
+            ),
+            BuiltinColorscheme::Deuteranopia => match shape {
+                // Okabe-Ito palette, which stays apart for red-green deficiencies
+                Shape::L => Color::from_rgb(230, 159, 0), // Orange
+                Shape::J => Color::from_rgb(0, 114, 178), // Blue
+                Shape::O => Color::from_rgb(240, 228, 66), // Yellow
+                Shape::T => Color::from_rgb(204, 121, 167), // Reddish purple
+                Shape::I => Color::from_rgb(86, 180, 233), // Sky blue
+                Shape::S => Color::from_rgb(0, 158, 115), // Bluish green
+                Shape::Z => Color::from_rgb(213, 94, 0),  // Vermillion
+            },
+            BuiltinColorscheme::Protanopia => match shape {
+                // Reds look dark without red cones, so the colors are told
+                // apart by blue against yellow and by their brightness.
+                Shape::L => Color::from_rgb(255, 176, 0), // Gold
+                Shape::J => Color::from_rgb(100, 143, 255), // Cornflower blue
+                Shape::O => Color::from_rgb(255, 240, 160), // Pale yellow
+                Shape::T => Color::from_rgb(170, 140, 255), // Lavender
+                Shape::I => Color::from_rgb(150, 220, 255), // Light sky blue
+                Shape::S => Color::from_rgb(160, 160, 160), // Gray
+                Shape::Z => Color::from_rgb(254, 97, 0),  // Orange
+            },
+            BuiltinColorscheme::Tritanopia => match shape {
+                // Blue and green as well as yellow and violet look alike
+                // without blue cones, so the colors are told apart by red
+                // against cyan and by their brightness.
+                Shape::L => Color::from_rgb(235, 70, 60), // Red
+                Shape::J => Color::from_rgb(0, 150, 160), // Teal
+                Shape::O => Color::from_rgb(245, 245, 245), // White
+                Shape::T => Color::from_rgb(255, 150, 200), // Pink
+                Shape::I => Color::from_rgb(130, 220, 235), // Light cyan
+                Shape::S => Color::from_rgb(150, 150, 150), // Gray
+                Shape::Z => Color::from_rgb(255, 200, 180), // Peach
+            },
+            BuiltinColorscheme::HighContrast => match shape {
+                // Fully saturated, bright colors
+                Shape::L => Color::from_rgb(255, 170, 0), // Orange
+                Shape::J => Color::from_rgb(110, 170, 255), // Light blue
+                Shape::O => Color::from_rgb(255, 255, 0), // Yellow
+                Shape::T => Color::from_rgb(255, 120, 255), // Magenta
+                Shape::I => Color::from_rgb(0, 255, 255), // Cyan
+                Shape::S => Color::from_rgb(0, 255, 0),   // Green
+                Shape::Z => Color::from_rgb(255, 100, 100), // Light red
+            },
+            BuiltinColorscheme::Purple => match shape {
+                // Rich, deep purples and violet shades
+                Shape::L => Color::from_rgb(148, 0, 211), // Dark violet
+                Shape::J => Color::from_rgb(106, 13, 173), // Deep purple
+                Shape::O => Color::from_rgb(178, 102, 255), // Bright violet
+                Shape::T => Color::from_rgb(75, 0, 130),  // Indigo
+                Shape::I => Color::from_rgb(128, 0, 128), // Pure purple
+                Shape::S => Color::from_rgb(153, 50, 204), // Dark orchid
+                Shape::Z => Color::from_rgb(139, 0, 139), // Deep magenta
+            },
+        }
+    }
+}
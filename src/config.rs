@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Error, Result};
+use serde::Deserialize;
+use toml::{Spanned, Value};
+
+use crate::colorscheme::{ColorschemeConfig, CustomColorscheme};
+use crate::schedule::RuleConfig;
+
+/// Values of command line options by their long name, like `scale = 2` for
+/// `--scale 2`
+pub type Options = BTreeMap<String, Spanned<Value>>;
+
+/// Tables of the config file, which are not command line options
+#[derive(Debug, Default, Deserialize)]
+struct Sections {
+    #[serde(default)]
+    colorschemes: BTreeMap<String, Spanned<ColorschemeConfig>>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Options>,
+    #[serde(default, rename = "auto-colorscheme")]
+    auto_colorscheme: Vec<Spanned<RuleConfig>>,
+}
+
+/// Settings read from the config file.
+///
+/// ```toml
+/// colorscheme = "neon"
+/// scale = 2
+///
+/// [profiles.standup]
+/// countdown = "00:15:00"
+/// chrome = true
+/// ```
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Colorschemes selectable by their name besides the built-in ones
+    pub colorschemes: BTreeMap<String, ColorschemeConfig>,
+    /// Rules picking the colorscheme for `--colorscheme auto`
+    pub auto_colorscheme: Vec<Spanned<RuleConfig>>,
+    /// Defaults for the command line options
+    options: Options,
+    /// Options selected together with `--profile`, replacing the defaults
+    profiles: BTreeMap<String, Options>,
+    path: PathBuf,
+    content: String,
+}
+
+impl Config {
+    /// Location of the config file: `tetrotime/config.toml` in
+    /// `$XDG_CONFIG_HOME`, or in `~/.config` if that is not set.
+    pub fn path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_home.join("tetrotime").join("config.toml"))
+    }
+
+    /// Read the config file at its default location. Without a config file
+    /// the default settings are used.
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => Self::load_file(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read config file {}", path.display()))?;
+        let mut config = Self {
+            path: path.to_path_buf(),
+            content,
+            ..Self::default()
+        };
+
+        let parse_error = |error: toml::de::Error| match error.span() {
+            Some(span) => config.error(span, error.message()),
+            None => anyhow!(
+                "Invalid config file {}: {}",
+                path.display(),
+                error.message()
+            ),
+        };
+        let mut options: Options = toml::from_str(&config.content).map_err(parse_error)?;
+        let sections: Sections = toml::from_str(&config.content).map_err(parse_error)?;
+        options.remove("colorschemes");
+        options.remove("profiles");
+        options.remove("auto-colorscheme");
+
+        // Colorschemes are validated right away, not only once they are
+        // selected, to tell about mistakes in any of them.
+        for (name, colorscheme) in sections.colorschemes {
+            CustomColorscheme::new(&name, colorscheme.get_ref())
+                .map_err(|error| config.error(colorscheme.span(), format!("{:#}", error)))?;
+            config.colorschemes.insert(name, colorscheme.into_inner());
+        }
+        config.profiles = sections.profiles;
+        config.auto_colorscheme = sections.auto_colorscheme;
+        config.options = options;
+        Ok(config)
+    }
+
+    /// Options of the given profile, falling back to the defaults for every
+    /// option the profile does not set.
+    pub fn options(&self, profile: Option<&str>) -> Result<Options> {
+        let mut options = self.options.clone();
+        if let Some(name) = profile {
+            let profile = self.profiles.get(name).ok_or_else(|| {
+                let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                anyhow!(
+                    "Unknown profile '{}', available are: {}",
+                    name,
+                    if names.is_empty() {
+                        "none".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                )
+            })?;
+            options.extend(profile.clone());
+        }
+        Ok(options)
+    }
+
+    /// Error about the part of the config file at the given position, telling
+    /// the line it is found in.
+    pub fn error(&self, span: Range<usize>, message: impl std::fmt::Display) -> Error {
+        let line = self.content[..span.start.min(self.content.len())]
+            .matches('\n')
+            .count()
+            + 1;
+        anyhow!(
+            "Invalid config file {}, line {}: {}",
+            self.path.display(),
+            line,
+            message
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Config file in the temporary directory, which is removed again once
+    /// it is dropped. Every file gets a path of its own, as tests run in
+    /// parallel.
+    pub(crate) struct TempConfig {
+        pub path: PathBuf,
+    }
+
+    impl TempConfig {
+        pub fn new(content: &str) -> Self {
+            static COUNT: AtomicUsize = AtomicUsize::new(0);
+            let name = format!(
+                "tetrotime-{}-{}.toml",
+                std::process::id(),
+                COUNT.fetch_add(1, Ordering::Relaxed)
+            );
+            let path = std::env::temp_dir().join(name);
+            std::fs::write(&path, content).unwrap();
+            Self { path }
+        }
+    }
+
+    impl Drop for TempConfig {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    /// Load a config file with the given content.
+    fn load(content: &str) -> Result<Config> {
+        Config::load_file(&TempConfig::new(content).path)
+    }
+
+    #[test]
+    fn rejects_invalid_colorschemes_when_loading() {
+        let error = load("scale = 2\n\
+             \n\
+             [colorschemes.valid]\n\
+             digits = ['#000000', '#111111', '#222222', '#333333', '#444444', '#555555', '#666666', '#777777', '#888888', '#999999']\n\
+             \n\
+             [colorschemes.unused]\n\
+             positions = ['#000000']\n",
+        )
+        .unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("line 6"), "{}", message);
+        assert!(
+            message.contains("Invalid colorscheme 'unused'"),
+            "{}",
+            message
+        );
+        assert!(message.contains("6 colors are needed"), "{}", message);
+    }
+
+    #[test]
+    fn keeps_valid_colorschemes() {
+        let config = load(
+            "[colorschemes.mono]\n\
+             positions = ['#ffffff', '#ffffff', '#ffffff', '#ffffff', '#ffffff', '#ffffff']\n",
+        )
+        .unwrap();
+        assert!(config.colorschemes.contains_key("mono"));
+    }
+}
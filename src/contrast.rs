@@ -0,0 +1,91 @@
+use pixel_loop::color::Color;
+
+use crate::background::Background;
+use crate::colorscheme::Colorscheme;
+
+/// Contrast graphical objects need against their surroundings to be
+/// recognized, following WCAG 2.1 (non-text contrast)
+const MINIMUM_RATIO: f64 = 3.0;
+
+/// Relative luminance of the color as defined by WCAG, from 0.0 for black to
+/// 1.0 for white.
+fn luminance(color: &Color) -> f64 {
+    let linear = |channel: u8| {
+        let channel = channel as f64 / 255.0;
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b)
+}
+
+/// Contrast ratio of two colors as defined by WCAG, from 1.0 for equal colors
+/// to 21.0 for black and white.
+pub fn ratio(a: &Color, b: &Color) -> f64 {
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Whether the contrast ratio is too low for tetrominos to stand out.
+fn is_too_low(contrast: f64) -> bool {
+    contrast < MINIMUM_RATIO
+}
+
+fn hex(color: &Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// Print the contrast of every color of the colorscheme against the
+/// background. Against gradients the end with the lower contrast counts.
+pub fn report(name: &str, colorscheme: &Colorscheme, background: &Background) {
+    let backgrounds = background.colors();
+    println!(
+        "{} (background {})",
+        name,
+        backgrounds.iter().map(hex).collect::<Vec<_>>().join(" to ")
+    );
+    for (what, color) in colorscheme.named_colors() {
+        let contrast = backgrounds
+            .iter()
+            .map(|background| ratio(&color, background))
+            .fold(f64::INFINITY, f64::min);
+        println!(
+            "  {:<12} {} {:>5.1}:1{}",
+            what,
+            hex(&color),
+            contrast,
+            if is_too_low(contrast) {
+                "  too low"
+            } else {
+                ""
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_black_and_white_highest() {
+        let black = Color::from_rgb(0, 0, 0);
+        let white = Color::from_rgb(255, 255, 255);
+        assert!((ratio(&black, &white) - 21.0).abs() < 1e-9);
+        assert!((ratio(&white, &black) - 21.0).abs() < 1e-9);
+        assert!((ratio(&white, &white) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn needs_a_ratio_of_three() {
+        let white = Color::from_rgb(255, 255, 255);
+        // #949494 is the lightest gray just reaching 3:1 against white.
+        let enough = ratio(&Color::from_rgb(0x94, 0x94, 0x94), &white);
+        let too_low = ratio(&Color::from_rgb(0x95, 0x95, 0x95), &white);
+        assert!(!is_too_low(enough), "{}", enough);
+        assert!(is_too_low(too_low), "{}", too_low);
+        assert!(!is_too_low(MINIMUM_RATIO));
+    }
+}
@@ -0,0 +1,425 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use pixel_loop::canvas::{Canvas, CrosstermCanvas, RenderableCanvas};
+use pixel_loop::color::Color;
+use pixel_loop::input::{CrosstermInputState, KeyboardKey, KeyboardState};
+use pixel_loop::NextLoopState;
+
+use crate::animation::{Digit, FallingTetromino};
+use crate::colorscheme::Colorscheme;
+use crate::font::{Font, Glyph};
+use crate::paint::Painter;
+use crate::scene::CellCanvas;
+use crate::simulation::Validation;
+use crate::tetromino::{DigitBoard, Rotation, Shape};
+use crate::text::{draw_text, text_width, CHAR_HEIGHT};
+
+/// Free space above the box of the glyph, where tetrominos enter the board
+const DROP_HEIGHT: i64 = 8;
+/// Horizontal position of the box the glyph is assembled in
+const BOX_X: i64 = 4;
+/// Vertical distance between two lines of text
+const LINE_HEIGHT: i64 = CHAR_HEIGHT + 2;
+
+const DIGIT_KEYS: [KeyboardKey; 10] = [
+    KeyboardKey::Zero,
+    KeyboardKey::One,
+    KeyboardKey::Two,
+    KeyboardKey::Three,
+    KeyboardKey::Four,
+    KeyboardKey::Five,
+    KeyboardKey::Six,
+    KeyboardKey::Seven,
+    KeyboardKey::Eight,
+    KeyboardKey::Nine,
+];
+
+const HELP: [&str; 7] = [
+    "TAB 0-9 GLYPH",
+    "S SHAPE  R ROTATE",
+    "<> MOVE  SPACE DROP",
+    "U UNDO   X CLEAR",
+    "P REPLAY",
+    "B USE AS BITMAP",
+    "W SAVE   Q QUIT",
+];
+
+struct Editor {
+    font: Font,
+    path: PathBuf,
+    colorscheme: Colorscheme,
+    /// Font the preview boards are created with. The glyphs shown are taken
+    /// from `font`, which holds all edits.
+    board_font: Rc<Font>,
+    glyph: usize,
+    shape: Shape,
+    rotation: Rotation,
+    dx: i64,
+    board: DigitBoard,
+    validation: Validation,
+    /// Earlier states of every glyph, restored one after the other by undo
+    history: Vec<Vec<Glyph>>,
+    modified: bool,
+    message: String,
+    confirm_quit: bool,
+}
+
+impl Editor {
+    fn new(font: Font, path: PathBuf, colorscheme: Colorscheme) -> Self {
+        let board_font = Rc::new(font.clone());
+        let board = DigitBoard::new(
+            0,
+            BOX_X,
+            DROP_HEIGHT + font.height as i64,
+            colorscheme.clone(),
+            board_font.clone(),
+            Digit::Zero,
+        );
+        let validation = font.validate(&font.glyphs[0]);
+        let mut editor = Self {
+            history: vec![vec![]; font.glyphs.len()],
+            font,
+            path,
+            colorscheme,
+            board_font,
+            glyph: 0,
+            shape: Shape::L,
+            rotation: Rotation::NoRotation,
+            dx: 0,
+            board,
+            validation,
+            modified: false,
+            message: String::new(),
+            confirm_quit: false,
+        };
+        editor.select_glyph(0);
+        editor
+    }
+
+    fn y_stop(&self) -> i64 {
+        DROP_HEIGHT + self.font.height as i64
+    }
+
+    fn current(&self) -> &Glyph {
+        &self.font.glyphs[self.glyph]
+    }
+
+    // Glyphs, which are not named after a digit, are colored like a zero.
+    fn digit(&self) -> Digit {
+        self.current()
+            .name
+            .parse::<u32>()
+            .ok()
+            .filter(|&numeral| numeral < 10)
+            .map(Digit::from)
+            .unwrap_or(Digit::Zero)
+    }
+
+    fn select_glyph(&mut self, index: usize) {
+        self.glyph = index;
+        self.board = DigitBoard::new(
+            0,
+            BOX_X,
+            self.y_stop(),
+            self.colorscheme.clone(),
+            self.board_font.clone(),
+            self.digit(),
+        );
+        self.board.set_animation(self.current().tetrominos.clone());
+        self.validation = self.font.validate(self.current());
+    }
+
+    /// Modify the current glyph, while remembering its previous state.
+    fn change<F: FnOnce(&mut Glyph)>(&mut self, change: F) {
+        let previous = self.current().clone();
+        self.history[self.glyph].push(previous);
+        change(&mut self.font.glyphs[self.glyph]);
+        self.modified = true;
+        self.validation = self.font.validate(self.current());
+    }
+
+    fn undo(&mut self) {
+        let Some(glyph) = self.history[self.glyph].pop() else {
+            self.message = "NOTHING TO UNDO".to_string();
+            return;
+        };
+        self.font.glyphs[self.glyph] = glyph;
+        self.board.set_animation(self.current().tetrominos.clone());
+        self.validation = self.font.validate(self.current());
+    }
+
+    fn drop_tetromino(&mut self) {
+        let tetromino = FallingTetromino::new(self.shape, self.rotation, self.dx);
+        self.change(|glyph| glyph.tetrominos.push(tetromino));
+        self.board.push_tetromino(tetromino);
+    }
+
+    /// Keep all cells of the selected tetromino within the box of the glyph.
+    fn clamp_column(&mut self) {
+        let cells = self.shape.cells(self.rotation);
+        let min = cells.iter().map(|&(x, _)| x).min().unwrap();
+        let max = cells.iter().map(|&(x, _)| x).max().unwrap();
+        self.dx = self.dx.min(self.font.width as i64 - 1 - max).max(-min);
+    }
+
+    fn next_shape(&mut self) {
+        let index = Shape::ALL.iter().position(|&shape| shape == self.shape);
+        self.shape = Shape::ALL[index.map_or(0, |index| (index + 1) % Shape::ALL.len())];
+        self.rotation = Rotation::NoRotation;
+        self.clamp_column();
+    }
+
+    fn rotate(&mut self) {
+        let rotations = self.shape.distinct_rotations();
+        let index = rotations
+            .iter()
+            .position(|&rotation| rotation == self.rotation);
+        self.rotation = rotations[index.map_or(0, |index| (index + 1) % rotations.len())];
+        self.clamp_column();
+    }
+
+    fn save(&mut self) {
+        match self.font.save(&self.path) {
+            Ok(()) => {
+                self.modified = false;
+                self.message = "SAVED".to_string();
+            }
+            // The path is known already, the cause tells what went wrong.
+            Err(error) => self.message = format!("COULD NOT SAVE: {}", error.root_cause()),
+        }
+    }
+
+    /// Act upon the pressed keys. Returns true, once the editor should quit.
+    fn handle_input<I: KeyboardState>(&mut self, input: &I) -> bool {
+        if input.is_key_pressed(KeyboardKey::Q) || input.is_key_pressed(KeyboardKey::Escape) {
+            if !self.modified || self.confirm_quit {
+                return true;
+            }
+            self.confirm_quit = true;
+            self.message = "UNSAVED! Q TO QUIT".to_string();
+            return false;
+        }
+
+        let before = (self.glyph, self.shape, self.rotation, self.dx);
+        let history: usize = self.history.iter().map(|glyphs| glyphs.len()).sum();
+
+        if input.is_key_pressed(KeyboardKey::Tab) {
+            self.select_glyph((self.glyph + 1) % self.font.glyphs.len());
+        }
+        for (numeral, &key) in DIGIT_KEYS.iter().enumerate() {
+            if input.is_key_pressed(key) {
+                if let Some(index) = self
+                    .font
+                    .glyphs
+                    .iter()
+                    .position(|glyph| glyph.name == numeral.to_string())
+                {
+                    self.select_glyph(index);
+                }
+            }
+        }
+        if input.is_key_pressed(KeyboardKey::S) {
+            self.next_shape();
+        }
+        if input.is_key_pressed(KeyboardKey::R) || input.is_key_pressed(KeyboardKey::Up) {
+            self.rotate();
+        }
+        if input.is_key_pressed(KeyboardKey::Left) {
+            self.dx -= 1;
+            self.clamp_column();
+        }
+        if input.is_key_pressed(KeyboardKey::Right) {
+            self.dx += 1;
+            self.clamp_column();
+        }
+        if input.is_key_pressed(KeyboardKey::Space)
+            || input.is_key_pressed(KeyboardKey::Enter)
+            || input.is_key_pressed(KeyboardKey::Down)
+        {
+            self.drop_tetromino();
+        }
+        if input.is_key_pressed(KeyboardKey::U) || input.is_key_pressed(KeyboardKey::Backspace) {
+            self.undo();
+        }
+        if input.is_key_pressed(KeyboardKey::X) {
+            self.change(|glyph| glyph.tetrominos.clear());
+            self.board.set_animation(vec![]);
+        }
+        if input.is_key_pressed(KeyboardKey::P) {
+            self.board.replay();
+        }
+        if input.is_key_pressed(KeyboardKey::B) {
+            let bitmap = self.validation.result.clone();
+            self.change(|glyph| glyph.bitmap = Some(bitmap));
+        }
+        if input.is_key_pressed(KeyboardKey::W) {
+            self.save();
+        }
+
+        let history_now: usize = self.history.iter().map(|glyphs| glyphs.len()).sum();
+        if before != (self.glyph, self.shape, self.rotation, self.dx) || history != history_now {
+            self.confirm_quit = false;
+            self.message.clear();
+        }
+
+        false
+    }
+
+    fn render<C: CellCanvas>(&self, canvas: &mut C) {
+        let width = self.font.width as i64;
+        let height = self.font.height as i64;
+        let y_stop = self.y_stop();
+        let frame = Color::from_rgb(80, 80, 80);
+        let text = Color::from_rgb(200, 200, 200);
+        let color = self.colorscheme.apply(self.shape, self.digit(), 0);
+
+        // Frame around the box the tetrominos are dropped into
+        canvas.filled_rect(BOX_X - 1, y_stop - height, 1, height as u32 + 1, &frame);
+        canvas.filled_rect(BOX_X + width, y_stop - height, 1, height as u32 + 1, &frame);
+        canvas.filled_rect(BOX_X - 1, y_stop, width as u32 + 2, 1, &frame);
+        self.board.render(canvas, &Painter::default());
+
+        // Columns the selected tetromino is dropped into
+        for (x, _) in self.shape.cells(self.rotation) {
+            canvas.filled_rect(BOX_X + self.dx + x, y_stop + 2, 1, 1, &color);
+        }
+
+        // The bitmap of the glyph below its box, showing cells still missing
+        // in gray and cells, which should stay empty, in red.
+        let bitmap = self.current().bitmap.as_ref();
+        for y in 0..height {
+            for x in 0..width {
+                let expected = bitmap.is_some_and(|bitmap| bitmap.get(x, y));
+                let covered = self.validation.result.get(x, y);
+                let cell_color = match (expected, covered) {
+                    (true, true) => Color::from_rgb(60, 140, 60),
+                    (true, false) => Color::from_rgb(100, 100, 100),
+                    (false, true) if bitmap.is_some() => Color::from_rgb(180, 40, 40),
+                    (false, true) => Color::from_rgb(100, 100, 100),
+                    (false, false) => continue,
+                };
+                canvas.filled_rect(BOX_X + x, y_stop + 4 + y, 1, 1, &cell_color);
+            }
+        }
+
+        let x = BOX_X + width + 4;
+        let glyph = self.current();
+        let status = if self.validation.problems.is_empty() {
+            ("OK".to_string(), Color::from_rgb(60, 180, 60))
+        } else {
+            (
+                format!("{} PROBLEMS", self.validation.problems.len()),
+                Color::from_rgb(200, 60, 60),
+            )
+        };
+        let lines = [
+            (
+                format!(
+                    "{} {}X{}{}",
+                    self.font.name,
+                    width,
+                    height,
+                    if self.modified { " *" } else { "" }
+                ),
+                text,
+            ),
+            (
+                format!(
+                    "GLYPH {} ({}/{})",
+                    glyph.name,
+                    self.glyph + 1,
+                    self.font.glyphs.len()
+                ),
+                text,
+            ),
+            (format!("{} TETROMINOS", glyph.tetrominos.len()), text),
+            status,
+            (
+                format!(
+                    "{:?} {} COLUMN {}",
+                    self.shape,
+                    u16::from(self.rotation),
+                    self.dx
+                ),
+                text,
+            ),
+            (
+                fit_text(&self.message, canvas.width() as i64 - x),
+                Color::from_rgb(230, 200, 60),
+            ),
+        ];
+        for (i, (line, color)) in lines.iter().enumerate() {
+            draw_text(canvas, x, 1 + i as i64 * LINE_HEIGHT, line, color);
+        }
+
+        // Preview of the selected tetromino
+        let preview_y = 1 + lines.len() as i64 * LINE_HEIGHT + 4;
+        for (cell_x, cell_y) in self.shape.cells(self.rotation) {
+            canvas.filled_rect(x + 1 + cell_x, preview_y + cell_y, 1, 1, &color);
+        }
+
+        let help_y = preview_y + 2;
+        for (i, line) in HELP.iter().enumerate() {
+            draw_text(canvas, x, help_y + i as i64 * LINE_HEIGHT, line, &frame);
+        }
+    }
+}
+
+/// Shorten the text to the given width in pixels, ending it with `..` if
+/// it needs to be cut off.
+fn fit_text(text: &str, width: i64) -> String {
+    if text_width(text) <= width {
+        return text.to_string();
+    }
+    let mut fitting: String = text.chars().collect();
+    while !fitting.is_empty() && text_width(&format!("{}..", fitting)) > width {
+        fitting.pop();
+    }
+    format!("{}..", fitting)
+}
+
+/// Run the interactive font editor, which works on the given font and saves
+/// it to the given path.
+pub fn run(font: Font, path: PathBuf, colorscheme: Colorscheme) -> ! {
+    let canvas = CrosstermCanvas::new();
+    let input = CrosstermInputState::new();
+
+    let editor = Editor::new(font, path, colorscheme);
+
+    pixel_loop::run(
+        30,
+        editor,
+        input,
+        canvas,
+        |_, editor, input, canvas| {
+            if editor.handle_input(input) {
+                return Ok(NextLoopState::Exit(0));
+            }
+
+            editor.board.update(canvas.height());
+
+            Ok(NextLoopState::Continue)
+        },
+        |_, editor, _, canvas, _| {
+            canvas.clear_screen(&Color::from_rgb(0, 0, 0));
+            editor.render(canvas);
+            canvas.render()?;
+
+            Ok(NextLoopState::Continue)
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cuts_off_text_at_the_given_width() {
+        assert_eq!(fit_text("SAVED", 100), "SAVED");
+        let text = fit_text("COULD NOT SAVE: Permission denied (os error 13)", 60);
+        assert_eq!(text, "COULD NOT SAV..");
+        assert!(text_width(&text) <= 60);
+    }
+}
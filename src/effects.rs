@@ -0,0 +1,144 @@
+use clap::ValueEnum;
+use pixel_loop::canvas::Canvas;
+use pixel_loop::color::Color;
+
+use crate::scene::{Cell, Scene};
+
+/// How much the background is darkened right below a tetromino
+const SHADOW_STRENGTH: f32 = 0.6;
+
+/// How much of the light of the tetrominos is added to their surroundings
+const GLOW_STRENGTH: f32 = 0.9;
+
+/// Post-processing pass over the whole canvas, once the scene is drawn.
+#[derive(Debug, Copy, Clone, PartialEq, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum Effect {
+    /// Soft shadow below and to the right of every tetromino, shown on
+    /// backgrounds brighter than black
+    Shadow,
+    /// Light of the tetrominos shining onto their surroundings, made for
+    /// bright colorschemes like neon and matrix
+    Glow,
+}
+
+/// Pixels of the canvas as color channels from 0.0 to 255.0
+type Buffer = Vec<[f32; 3]>;
+
+/// Apply the effects one after the other to the canvas, onto which the scene
+/// has been drawn with the given scale. Effects only change the pixels of the
+/// canvas, which are not covered by the scene.
+pub fn apply<C: Canvas>(effects: &[Effect], scene: &Scene, scale: u32, canvas: &mut C) {
+    if effects.is_empty() {
+        return;
+    }
+
+    let width = canvas.width() as usize;
+    let height = canvas.height() as usize;
+    let covered: Vec<bool> = (0..width * height)
+        .map(|i| {
+            let x = (i % width) as u32 / scale;
+            let y = (i / width) as u32 / scale;
+            scene.cell(x as i64, y as i64) != Cell::Empty
+        })
+        .collect();
+
+    let mut pixels: Buffer = canvas
+        .get_range(0..width * height)
+        .iter()
+        .map(|color| [color.r as f32, color.g as f32, color.b as f32])
+        .collect();
+
+    // Everything is measured in cells, to look the same at every scale.
+    let cell = scale as usize;
+    for effect in effects {
+        match effect {
+            Effect::Shadow => {
+                // The shadow is the shape of the scene moved by one cell, with
+                // its edges blurred.
+                let mask: Buffer = (0..width * height)
+                    .map(|i| {
+                        let (x, y) = (i % width, i / width);
+                        let shadow = x >= cell && y >= cell && covered[i - cell * width - cell];
+                        [if shadow { 1.0 } else { 0.0 }; 3]
+                    })
+                    .collect();
+                let mask = blur(&mask, width, height, cell.div_ceil(2));
+                for (i, pixel) in pixels.iter_mut().enumerate() {
+                    if !covered[i] {
+                        let brightness = 1.0 - SHADOW_STRENGTH * mask[i][0];
+                        pixel.iter_mut().for_each(|c| *c *= brightness);
+                    }
+                }
+            }
+            Effect::Glow => {
+                let light: Buffer = pixels
+                    .iter()
+                    .zip(covered.iter())
+                    .map(|(pixel, &covered)| if covered { *pixel } else { [0.0; 3] })
+                    .collect();
+                // Blurring twice gives a softer falloff than a single box.
+                let light = blur(&light, width, height, cell);
+                let light = blur(&light, width, height, cell);
+                for (i, pixel) in pixels.iter_mut().enumerate() {
+                    if !covered[i] {
+                        for (c, light) in pixel.iter_mut().zip(light[i]) {
+                            *c = (*c + GLOW_STRENGTH * light).min(255.0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let colors: Vec<Color> = pixels
+        .iter()
+        .map(|[r, g, b]| Color::from_rgb(*r as u8, *g as u8, *b as u8))
+        .collect();
+    canvas.set_range(0..width * height, &colors);
+}
+
+/// Average every pixel with its neighbours up to the given distance
+/// horizontally and vertically (a box blur).
+fn blur(buffer: &Buffer, width: usize, height: usize, radius: usize) -> Buffer {
+    let horizontal = blur_lines(buffer, height, width, 1, width, radius);
+    blur_lines(&horizontal, width, height, width, 1, radius)
+}
+
+/// Box blur along `count` lines of `length` pixels. Consecutive pixels of a
+/// line are `step` apart in the buffer, while the first pixels of consecutive
+/// lines are `stride` apart. Pixels outside of the buffer count as black.
+fn blur_lines(
+    buffer: &Buffer,
+    count: usize,
+    length: usize,
+    step: usize,
+    stride: usize,
+    radius: usize,
+) -> Buffer {
+    let mut result = vec![[0.0; 3]; buffer.len()];
+    let size = (2 * radius + 1) as f32;
+    for line in 0..count {
+        let index = |i: usize| line * stride + i * step;
+        let mut sum = [0.0f32; 3];
+        for i in 0..radius.min(length) {
+            sum.iter_mut()
+                .zip(buffer[index(i)])
+                .for_each(|(s, c)| *s += c);
+        }
+        for i in 0..length {
+            if i + radius < length {
+                sum.iter_mut()
+                    .zip(buffer[index(i + radius)])
+                    .for_each(|(s, c)| *s += c);
+            }
+            result[index(i)] = sum.map(|s| s / size);
+            if i >= radius {
+                sum.iter_mut()
+                    .zip(buffer[index(i - radius)])
+                    .for_each(|(s, c)| *s -= c);
+            }
+        }
+    }
+    result
+}
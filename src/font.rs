@@ -0,0 +1,208 @@
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::animation::{Animation, Digit};
+use crate::bitmap::Bitmap;
+use crate::simulation::{self, Problem, Validation};
+
+const BUILTIN: &str = include_str!("../assets/fonts/default.toml");
+const BUILTIN_SMALL: &str = include_str!("../assets/fonts/small.toml");
+const BUILTIN_LARGE: &str = include_str!("../assets/fonts/large.toml");
+
+/// Size of the digits, selecting one of the built-in fonts.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum DigitSize {
+    /// Largest size fitting into the terminal
+    #[default]
+    Auto,
+    /// Digits of 4x7 pixels
+    Small,
+    /// Digits of 6x10 pixels (the default font)
+    Medium,
+    /// Digits of 10x16 pixels
+    Large,
+}
+
+/// A set of glyphs, which are assembled from falling tetrominos.
+///
+/// Fonts are stored as TOML files. The built-in font in
+/// `assets/fonts/default.toml` documents the format.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Font {
+    pub name: String,
+    /// Width of the box, every glyph is assembled in
+    pub width: usize,
+    /// Height of the box, every glyph is assembled in
+    pub height: usize,
+    pub glyphs: Vec<Glyph>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Glyph {
+    /// Name of the glyph. Digits are named by their numeral (eg. "7").
+    pub name: String,
+    /// The intended result of the animation. Only used for validation.
+    #[serde(default)]
+    pub bitmap: Option<Bitmap>,
+    /// Tetrominos dropped one after the other to assemble the glyph
+    pub tetrominos: Animation,
+}
+
+impl Font {
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN).expect("built-in font is valid")
+    }
+
+    /// The built-in fonts of the given size, ordered from small to large.
+    pub fn builtin_sized(size: DigitSize) -> Vec<Self> {
+        let sources: &[&str] = match size {
+            DigitSize::Auto => &[BUILTIN_SMALL, BUILTIN, BUILTIN_LARGE],
+            DigitSize::Small => &[BUILTIN_SMALL],
+            DigitSize::Medium => &[BUILTIN],
+            DigitSize::Large => &[BUILTIN_LARGE],
+        };
+        sources
+            .iter()
+            .map(|source| Self::parse(source).expect("built-in font is valid"))
+            .collect()
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read font file {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid font file {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let font: Font = toml::from_str(text)?;
+
+        if font.width == 0 || font.height == 0 {
+            return Err(anyhow!(
+                "Font needs a width and height of at least 1. Got {}x{}",
+                font.width,
+                font.height
+            ));
+        }
+
+        for glyph in font.glyphs.iter() {
+            if let Some(bitmap) = &glyph.bitmap {
+                if bitmap.width() != font.width || bitmap.height() != font.height {
+                    return Err(anyhow!(
+                        "Bitmap of glyph '{}' is {}x{}, but the font is {}x{}",
+                        glyph.name,
+                        bitmap.width(),
+                        bitmap.height(),
+                        font.width,
+                        font.height
+                    ));
+                }
+            }
+        }
+
+        for digit in Digit::ALL {
+            if font.glyph(&digit.to_string()).is_none() {
+                return Err(anyhow!("Font has no glyph for the digit {}", digit));
+            }
+        }
+
+        Ok(font)
+    }
+
+    /// A font with an empty glyph for every digit.
+    pub fn empty(name: &str, width: usize, height: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            width,
+            height,
+            glyphs: Digit::ALL
+                .iter()
+                .map(|digit| Glyph {
+                    name: digit.to_string(),
+                    bitmap: None,
+                    tetrominos: vec![],
+                })
+                .collect(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_string())
+            .with_context(|| format!("Could not write font file {}", path.display()))
+    }
+
+    pub fn glyph(&self, name: &str) -> Option<&Glyph> {
+        self.glyphs.iter().find(|glyph| glyph.name == name)
+    }
+
+    pub fn digit_glyph(&self, digit: Digit) -> &Glyph {
+        self.glyph(&digit.to_string())
+            .expect("fonts contain a glyph for every digit")
+    }
+
+    pub fn animation(&self, digit: Digit) -> &Animation {
+        &self.digit_glyph(digit).tetrominos
+    }
+
+    /// Simulate the animation of a glyph and check its result.
+    ///
+    /// Glyphs without a bitmap can only be checked for overlapping or floating
+    /// tetrominos, as well as tetrominos outside of the box of the font.
+    pub fn validate(&self, glyph: &Glyph) -> Validation {
+        match &glyph.bitmap {
+            Some(bitmap) => simulation::validate(&glyph.tetrominos, bitmap),
+            None => {
+                let box_bitmap = Bitmap::filled(self.width, self.height);
+                let mut validation = simulation::validate(&glyph.tetrominos, &box_bitmap);
+                validation
+                    .problems
+                    .retain(|problem| !matches!(problem, Problem::Missing { .. }));
+                validation
+            }
+        }
+    }
+}
+
+impl fmt::Display for Font {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "name = {}", toml::Value::String(self.name.clone()))?;
+        writeln!(f, "width = {}", self.width)?;
+        writeln!(f, "height = {}", self.height)?;
+        for glyph in self.glyphs.iter() {
+            write!(f, "\n{}", glyph)?;
+        }
+        Ok(())
+    }
+}
+
+// Glyphs are written in the same layout used by the font files shipped with
+// tetrotime, so that they can be pasted into them.
+impl fmt::Display for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "[[glyphs]]")?;
+        writeln!(f, "name = {}", toml::Value::String(self.name.clone()))?;
+        if let Some(bitmap) = &self.bitmap {
+            write!(f, "bitmap = \"\"\"\n{}\"\"\"\n", bitmap)?;
+        }
+        if self.tetrominos.is_empty() {
+            return writeln!(f, "tetrominos = []");
+        }
+        writeln!(f, "tetrominos = [")?;
+        for tetromino in self.tetrominos.iter() {
+            writeln!(
+                f,
+                "    {{ shape = \"{:?}\", rotation = {}, dx = {} }},",
+                tetromino.shape,
+                u16::from(tetromino.rotation),
+                tetromino.dx
+            )?;
+        }
+        writeln!(f, "]")
+    }
+}
@@ -0,0 +1,516 @@
+use pixel_loop::color::Color;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::animation::Digit;
+use crate::colorscheme::Colorscheme;
+use crate::scene::CellCanvas;
+use crate::tetromino::{dim, Board, Placement, Rotation, Shape};
+
+/// Width of the well in cells, as in the original game
+pub const WIDTH: i64 = 10;
+
+/// Brightness of the game, to keep it in the background of the clock
+const BRIGHTNESS: f64 = 0.35;
+
+/// Number of updates it takes the current tetromino to fall down by one row,
+/// while the computer plays
+const UPDATES_PER_ROW: usize = 2;
+
+/// Number of updates it takes the current tetromino to fall down by one row
+/// on the first level of a game played by a person. Every level is a bit
+/// faster.
+const PLAYER_UPDATES_PER_ROW: usize = 12;
+
+/// Number of cleared lines needed to reach the next level
+const LINES_PER_LEVEL: usize = 10;
+
+/// Points for clearing one, two, three or four lines at once on the first
+/// level, like in the original game
+const LINE_POINTS: [usize; 4] = [40, 100, 300, 1200];
+
+// Weights of the properties of a well, which the computer player uses to rate
+// where to drop a tetromino. Fewer holes and a flat, low stack are better.
+const HEIGHT_WEIGHT: f64 = -0.51;
+const LINES_WEIGHT: f64 = 0.76;
+const HOLES_WEIGHT: f64 = -0.36;
+const BUMPINESS_WEIGHT: f64 = -0.18;
+
+/// Cell of a tetromino resting in the well
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    color: Color,
+    shape: Shape,
+    /// Number of the tetromino among all tetrominos put into the well, to
+    /// draw the cells of each one as a whole
+    piece: usize,
+}
+
+/// A game of Tetris, played by the computer unless a person takes over.
+///
+/// Tetrominos resting in the well are split apart by cleared lines, therefore
+/// the game keeps track of the occupied cells of its well itself. Cells are
+/// rows and columns of single pixels, using the same coordinates as
+/// tetrominos do. New tetrominos enter the well from above its first row.
+pub struct Game {
+    x_offset: i64,
+    y_offset: i64,
+    /// Every cell of the well, row by row from the top
+    cells: Vec<Vec<Option<Block>>>,
+    /// Number of tetrominos put into the well so far
+    locked: usize,
+    current: Placement,
+    /// Color of the current tetromino, taken from the colorscheme when it
+    /// entered the well
+    current_color: Color,
+    target: Placement,
+    /// Shapes coming next. Every shape is dealt once in random order, before
+    /// the bag is refilled.
+    bag: Vec<Shape>,
+    rng: StdRng,
+    colorscheme: Colorscheme,
+    board: Board,
+    updates: usize,
+    autoplay: bool,
+    score: usize,
+    lines: usize,
+}
+
+impl Game {
+    pub fn new(x_offset: i64, y_offset: i64, height: i64, colorscheme: Colorscheme) -> Self {
+        let mut board = Board::new(x_offset, y_offset, y_offset + height);
+        board.set_brightness(BRIGHTNESS);
+
+        let placement = Placement {
+            shape: Shape::O,
+            rotation: Rotation::NoRotation,
+            x: 0,
+            y: 0,
+        };
+        let mut game = Self {
+            x_offset,
+            y_offset,
+            cells: vec![vec![None; WIDTH as usize]; height.max(0) as usize],
+            locked: 0,
+            current: placement,
+            current_color: Color::from_rgb(0, 0, 0),
+            target: placement,
+            bag: vec![],
+            rng: StdRng::from_entropy(),
+            colorscheme,
+            board,
+            updates: 0,
+            autoplay: true,
+            score: 0,
+            lines: 0,
+        };
+        game.spawn();
+        game
+    }
+
+    /// Let a person play the game instead of the computer, using [Game::shift],
+    /// [Game::rotate] and the drop functions. The game is shown with its full
+    /// colors.
+    pub fn with_player(mut self) -> Self {
+        self.autoplay = false;
+        self.board.set_brightness(1.0);
+        self.reset();
+        self
+    }
+
+    /// Move the well to another place and change its height, keeping the
+    /// game going. Rows are added or removed at the top of the well.
+    pub fn move_to(&mut self, x_offset: i64, y_offset: i64, height: i64) {
+        let rows = height.max(0) - self.cells.len() as i64;
+        if rows > 0 {
+            let empty = vec![vec![None; WIDTH as usize]; rows as usize];
+            self.cells.splice(0..0, empty);
+        } else {
+            self.cells.drain(0..(-rows) as usize);
+        }
+        self.current.y += rows;
+        self.target.y += rows;
+
+        self.x_offset = x_offset;
+        self.y_offset = y_offset;
+        self.board = Board::new(x_offset, y_offset, y_offset + height);
+        self.board.set_brightness(self.brightness());
+    }
+
+    pub fn score(&self) -> usize {
+        self.score
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    pub fn level(&self) -> usize {
+        self.lines / LINES_PER_LEVEL
+    }
+
+    fn brightness(&self) -> f64 {
+        if self.autoplay {
+            BRIGHTNESS
+        } else {
+            1.0
+        }
+    }
+
+    fn updates_per_row(&self) -> usize {
+        if self.autoplay {
+            UPDATES_PER_ROW
+        } else {
+            PLAYER_UPDATES_PER_ROW.saturating_sub(self.level()).max(1)
+        }
+    }
+
+    /// Start over with an empty well.
+    fn reset(&mut self) {
+        for row in self.cells.iter_mut() {
+            row.fill(None);
+        }
+        self.score = 0;
+        self.lines = 0;
+        self.spawn();
+    }
+
+    /// Color tetrominos entering the well from now on with the given
+    /// colorscheme.
+    pub fn set_colorscheme(&mut self, colorscheme: Colorscheme) {
+        self.colorscheme = colorscheme;
+    }
+
+    /// Switch to the given colorscheme right away.
+    pub fn recolor(&mut self, colorscheme: Colorscheme) {
+        for block in self.cells.iter_mut().flatten().flatten() {
+            block.color = colorscheme.apply(block.shape, Digit::Zero, 0);
+        }
+        self.current_color = colorscheme.apply(self.current.shape, Digit::Zero, 0);
+        self.board
+            .recolor(|shape| colorscheme.apply(shape, Digit::Zero, 0));
+        self.colorscheme = colorscheme;
+    }
+
+    fn cells_of(placement: &Placement) -> impl Iterator<Item = (i64, i64)> {
+        let Placement { x, y, .. } = *placement;
+        placement
+            .shape
+            .cells(placement.rotation)
+            .into_iter()
+            .map(move |(dx, dy)| (x + dx, y + dy))
+    }
+
+    /// Whether the tetromino is inside of the well without covering any
+    /// occupied cell. Cells above the well are always free.
+    fn fits(cells: &[Vec<Option<Block>>], placement: &Placement) -> bool {
+        Self::cells_of(placement).all(|(x, y)| {
+            (0..WIDTH).contains(&x)
+                && y < cells.len() as i64
+                && (y < 0 || cells[y as usize][x as usize].is_none())
+        })
+    }
+
+    /// Move the tetromino down as far as possible.
+    fn dropped(cells: &[Vec<Option<Block>>], placement: &Placement) -> Placement {
+        let mut placement = *placement;
+        while Self::fits(
+            cells,
+            &Placement {
+                y: placement.y + 1,
+                ..placement
+            },
+        ) {
+            placement.y += 1;
+        }
+        placement
+    }
+
+    fn next_shape(&mut self) -> Shape {
+        if self.bag.is_empty() {
+            self.bag = Shape::ALL.to_vec();
+            self.bag.shuffle(&mut self.rng);
+        }
+        self.bag.pop().expect("the bag has just been refilled")
+    }
+
+    /// Let the next tetromino enter the well. The computer turns and moves
+    /// it into place above the first row of the well, while tetrominos of a
+    /// person start out in the well right away.
+    fn spawn(&mut self) {
+        let shape = self.next_shape();
+        self.current = Placement {
+            shape,
+            rotation: Rotation::NoRotation,
+            // Some shapes reach left of their coordinate.
+            x: WIDTH / 2 - 1,
+            y: if self.autoplay { 0 } else { 2 },
+        };
+        self.current_color = self.colorscheme.apply(shape, Digit::Zero, 0);
+        self.updates = 0;
+
+        if self.autoplay {
+            self.target = self.best_target(shape);
+        } else if !Self::fits(&self.cells, &self.current) {
+            self.reset();
+        }
+    }
+
+    /// Try every rotation and column for the shape and pick the one leaving
+    /// the best well behind.
+    fn best_target(&self, shape: Shape) -> Placement {
+        let mut best: Option<(f64, Placement)> = None;
+        for &rotation in shape.distinct_rotations() {
+            for x in -2..WIDTH + 2 {
+                let placement = Placement {
+                    shape,
+                    rotation,
+                    x,
+                    y: 0,
+                };
+                if !Self::fits(&self.cells, &placement) {
+                    continue;
+                }
+
+                let landed = Self::dropped(&self.cells, &placement);
+                let mut cells = self.cells.clone();
+                for (x, y) in Self::cells_of(&landed) {
+                    if y >= 0 {
+                        cells[y as usize][x as usize] = Some(Block {
+                            color: self.current_color,
+                            shape,
+                            piece: self.locked,
+                        });
+                    }
+                }
+                let lines = clear_lines(&mut cells);
+                let rating = rate(&cells, lines);
+                if best.is_none_or(|(best_rating, _)| rating > best_rating) {
+                    best = Some((rating, placement));
+                }
+            }
+        }
+
+        best.map_or(self.current, |(_, placement)| placement)
+    }
+
+    /// Put the current tetromino into the well for good. A tetromino, which
+    /// does not fit into the well anymore, ends the game and a new one starts
+    /// with an empty well.
+    fn lock(&mut self) {
+        let block = Block {
+            color: self.current_color,
+            shape: self.current.shape,
+            piece: self.locked,
+        };
+        self.locked += 1;
+        let mut overflow = false;
+        for (x, y) in Self::cells_of(&self.current) {
+            if y < 0 {
+                overflow = true;
+            } else {
+                self.cells[y as usize][x as usize] = Some(block);
+            }
+        }
+
+        if overflow {
+            self.reset();
+            return;
+        }
+
+        let lines = clear_lines(&mut self.cells);
+        if lines > 0 {
+            self.score += LINE_POINTS[lines.min(4) - 1] * (self.level() + 1);
+            self.lines += lines;
+        }
+
+        self.spawn();
+    }
+
+    /// Move the current tetromino, if it fits at its new place. Returns
+    /// whether it was moved.
+    fn try_move(&mut self, placement: Placement) -> bool {
+        if !Self::fits(&self.cells, &placement) {
+            return false;
+        }
+        self.current = placement;
+        true
+    }
+
+    /// Move the current tetromino to the left (negative) or right (positive).
+    pub fn shift(&mut self, dx: i64) {
+        self.try_move(Placement {
+            x: self.current.x + dx,
+            ..self.current
+        });
+    }
+
+    /// Turn the current tetromino to its next distinct rotation.
+    pub fn rotate(&mut self) {
+        let rotations = self.current.shape.distinct_rotations();
+        let index = rotations
+            .iter()
+            .position(|&rotation| rotation == self.current.rotation);
+        self.try_move(Placement {
+            rotation: rotations[index.map_or(0, |index| (index + 1) % rotations.len())],
+            ..self.current
+        });
+    }
+
+    /// Let the current tetromino fall down by one row right away.
+    pub fn soft_drop(&mut self) {
+        if self.try_move(Placement {
+            y: self.current.y + 1,
+            ..self.current
+        }) {
+            self.score += 1;
+            self.updates = 0;
+        }
+    }
+
+    /// Drop the current tetromino as far as possible and lock it in place.
+    pub fn hard_drop(&mut self) {
+        let dropped = Self::dropped(&self.cells, &self.current);
+        self.score += 2 * (dropped.y - self.current.y) as usize;
+        self.current = dropped;
+        self.lock();
+    }
+
+    pub fn update(&mut self) {
+        self.updates += 1;
+
+        // The computer turns and moves the tetromino into place above the
+        // well, one step per update, before it starts to fall.
+        if self.autoplay && self.current.rotation != self.target.rotation {
+            self.current.rotation = self.target.rotation;
+        } else if self.autoplay && self.current.x != self.target.x {
+            self.current.x += (self.target.x - self.current.x).signum();
+        } else if self.updates >= self.updates_per_row() {
+            self.updates = 0;
+            let below = Placement {
+                y: self.current.y + 1,
+                ..self.current
+            };
+            if Self::fits(&self.cells, &below) {
+                self.current = below;
+            } else {
+                self.lock();
+            }
+        }
+
+        self.board.clear();
+        let Placement {
+            shape,
+            rotation,
+            x,
+            y,
+        } = self.current;
+        self.board
+            .place_tetromino(x, y, self.current_color, shape, rotation);
+    }
+
+    pub fn render<C: CellCanvas>(&self, canvas: &mut C) {
+        let mut blocks: Vec<(usize, usize, &Block)> = self
+            .cells
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(x, block)| block.as_ref().map(|block| (x, y, block)))
+            })
+            .collect();
+        blocks.sort_by_key(|(_, _, block)| block.piece);
+
+        // Cells of a tetromino are drawn together, even if cleared lines
+        // split it apart.
+        for piece in blocks.chunk_by(|(_, _, a), (_, _, b)| a.piece == b.piece) {
+            canvas.set_piece(Some(piece[0].2.shape));
+            for (x, y, block) in piece {
+                canvas.filled_rect(
+                    self.x_offset + *x as i64,
+                    self.y_offset + *y as i64,
+                    1,
+                    1,
+                    &dim(&block.color, self.brightness()),
+                );
+            }
+        }
+        canvas.set_piece(None);
+
+        // Parts of the current tetromino, which are still above the well, are
+        // drawn as well. Anything shown above the well needs to be drawn
+        // afterwards.
+        self.board.render(canvas);
+    }
+}
+
+/// Remove all completely filled rows, letting the rows above fall down.
+/// Returns the number of removed rows.
+fn clear_lines(cells: &mut Vec<Vec<Option<Block>>>) -> usize {
+    let height = cells.len();
+    cells.retain(|row| row.iter().any(|cell| cell.is_none()));
+    let lines = height - cells.len();
+    for _ in 0..lines {
+        cells.insert(0, vec![None; WIDTH as usize]);
+    }
+    lines
+}
+
+fn rate(cells: &[Vec<Option<Block>>], lines: usize) -> f64 {
+    let columns: Vec<(usize, usize)> = (0..WIDTH as usize)
+        .map(|x| {
+            let top = cells
+                .iter()
+                .position(|row| row[x].is_some())
+                .unwrap_or(cells.len());
+            let holes = cells[top..].iter().filter(|row| row[x].is_none()).count();
+            (cells.len() - top, holes)
+        })
+        .collect();
+
+    let height: usize = columns.iter().map(|(height, _)| height).sum();
+    let holes: usize = columns.iter().map(|(_, holes)| holes).sum();
+    let bumpiness: usize = columns
+        .windows(2)
+        .map(|pair| pair[0].0.abs_diff(pair[1].0))
+        .sum();
+
+    HEIGHT_WEIGHT * height as f64
+        + LINES_WEIGHT * lines as f64
+        + HOLES_WEIGHT * holes as f64
+        + BUMPINESS_WEIGHT * bumpiness as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Which cells of the lowest rows of the well are occupied
+    fn floor(game: &Game) -> Vec<Vec<bool>> {
+        game.cells[game.cells.len() - 4..]
+            .iter()
+            .map(|row| row.iter().map(Option::is_some).collect())
+            .collect()
+    }
+
+    #[test]
+    fn keeps_the_game_going_when_moved() {
+        let mut game = Game::new(0, 0, 20, Colorscheme::default()).with_player();
+        game.hard_drop();
+        let score = game.score();
+        let stack = floor(&game);
+        assert!(stack.iter().flatten().any(|&occupied| occupied));
+
+        game.move_to(5, 2, 30);
+        assert_eq!(game.cells.len(), 30);
+        assert_eq!(floor(&game), stack);
+        assert_eq!(game.score(), score);
+
+        game.move_to(5, 2, 10);
+        assert_eq!(game.cells.len(), 10);
+        assert_eq!(floor(&game), stack);
+        assert_eq!(game.score(), score);
+    }
+}
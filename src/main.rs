@@ -1,16 +1,72 @@
 mod animation;
+mod background;
+mod bitmap;
+mod blocks;
+mod chrome;
+mod colorscheme;
+mod config;
+mod contrast;
+mod editor;
+mod effects;
+mod font;
+mod game;
+mod paint;
+mod play;
+mod scene;
+mod schedule;
+mod shuffle;
+mod simulation;
+mod solver;
+mod terminal;
 mod tetromino;
+mod text;
+mod theme;
+mod urgency;
 
 use animation::Digit;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use background::Background;
+use bitmap::Bitmap;
+use blocks::BlockStyle;
+use chrome::Chrome;
 use chrono::{DateTime, Duration, Local, NaiveTime, Timelike};
-use clap::{arg, Parser};
+use clap::builder::{PossibleValue, StringValueParser, TypedValueParser};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, Parser, Subcommand, ValueEnum};
+use colorscheme::{BuiltinColorscheme, Colorscheme};
+use config::Config;
+use effects::Effect;
+use font::{DigitSize, Font, Glyph};
+use game::Game;
+use paint::{ColorMode, Painter};
 use pixel_loop::canvas::CrosstermCanvas;
 use pixel_loop::canvas::{Canvas, RenderableCanvas};
 use pixel_loop::color::Color;
 use pixel_loop::input::{CrosstermInputState, KeyboardKey, KeyboardState};
 use pixel_loop::NextLoopState;
-use tetromino::{Board, Colorscheme, DigitBoard, Rotation, Shape};
+use play::Play;
+use scene::Scene;
+use schedule::Schedule;
+use shuffle::{Shuffle, Shuffler};
+use std::cell::RefCell;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use terminal::{ColorDepth, Terminal};
+use tetromino::{DigitBoard, Motion, SeparatorBoard, SeparatorStyle, Shape};
+use urgency::{Level, Urgency};
+
+/// Format a time string of six digits as HH:MM:SS.
+fn format_time_string<T: AsRef<str>>(time_string: T) -> String {
+    let time_string = time_string.as_ref();
+    format!(
+        "{}:{}:{}",
+        &time_string[0..2],
+        &time_string[2..4],
+        &time_string[4..6]
+    )
+}
 
 fn time_string_to_digits<T: AsRef<str>>(time_string: T) -> Vec<Digit> {
     time_string
@@ -20,19 +76,14 @@ fn time_string_to_digits<T: AsRef<str>>(time_string: T) -> Vec<Digit> {
         .collect()
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 enum Mode {
+    #[default]
     Clock,
     Countdown(DateTime<Local>),
     Stopwatch(DateTime<Local>),
 }
 
-impl Default for Mode {
-    fn default() -> Self {
-        Self::Clock
-    }
-}
-
 impl Mode {
     fn get_timestring(&self) -> String {
         match self {
@@ -57,76 +108,247 @@ impl Mode {
             }
         }
     }
+
+    /// Whether the shown time is still changing. A countdown stops once it
+    /// reached zero.
+    fn is_running(&self) -> bool {
+        match self {
+            Self::Countdown(end) => *end > Local::now(),
+            _ => true,
+        }
+    }
+
+    /// Whether the user may pass the time with a game of Tetris.
+    fn is_playable(&self) -> bool {
+        matches!(self, Self::Countdown(_)) && self.is_running()
+    }
+
+    /// Fraction of the second shown right now, which passed so far. The
+    /// separators blink and pulse along with it.
+    fn second_fraction(&self) -> f64 {
+        let millis = match self {
+            Self::Clock => Local::now().timestamp_subsec_millis() as i64,
+            // The remaining seconds are rounded down, so a second of the
+            // countdown starts just below the full second.
+            Self::Countdown(end) => {
+                let remaining = end.signed_duration_since(Local::now());
+                (1000 - remaining.num_milliseconds().rem_euclid(1000)) % 1000
+            }
+            Self::Stopwatch(start) => Local::now()
+                .signed_duration_since(*start)
+                .num_milliseconds()
+                .rem_euclid(1000),
+        };
+        millis as f64 / 1000.0
+    }
+}
+
+/// Horizontal space between the two digits of hours, minutes or seconds
+const DIGIT_SPACING: i64 = 2;
+/// Horizontal space between hours, minutes and seconds
+const SEPERATOR_SPACING: i64 = 6;
+
+/// Minimal horizontal space between a background game and the digits
+const GAME_SPACING: i64 = 4;
+
+/// Number of updates a notice stays in the corner
+const NOTICE_UPDATES: usize = 60;
+
+/// Width of all six digits including the space between them
+fn layout_width(font: &Font) -> i64 {
+    6 * font.width as i64 + 3 * DIGIT_SPACING + 2 * SEPERATOR_SPACING
 }
 
 struct State {
     digit_boards: Vec<DigitBoard>,
     current_digits: Vec<Digit>,
-    seperator_boards: Vec<Board>,
+    seperator_boards: Vec<SeparatorBoard>,
+    games: Vec<Game>,
+    background_game: bool,
+    chrome: Option<Chrome>,
+    show_chrome: bool,
+    ghost: bool,
+    /// Everything is drawn onto the scene first, which is scaled up to the
+    /// size of the canvas afterwards. Every pixel of the scene is one cell of
+    /// a tetromino.
+    scene: Scene,
+    scale: u32,
+    block_style: BlockStyle,
+    /// Shown behind the scene, wherever it is empty
+    background: Background,
+    /// Post-processing passes over the canvas
+    effects: Vec<Effect>,
+    color_mode: ColorMode,
+    /// Colors the tetrominos of the clock by the color mode
+    painter: Painter,
+    /// Colors of the countdown changing as it runs out
+    urgency: Option<Urgency>,
+    /// Shows the canvas on terminals without 24-bit colors
+    terminal: Option<Terminal>,
+    /// Game played by the user instead of showing the countdown
+    play: Option<Play>,
     last_update_time: DateTime<Local>,
     colorscheme: Colorscheme,
+    /// Rules changing the colorscheme over time
+    schedule: Option<Schedule>,
+    /// Colorschemes to cycle through with the keyboard, by their names
+    colorschemes: Vec<(String, Colorscheme)>,
+    /// Whether the background is chosen independently of the colorscheme
+    fixed_background: bool,
+    /// Text shown in the corner for the given number of updates
+    notice: Option<(String, usize)>,
     mode: Mode,
+    motion: Motion,
+    seperator_style: SeparatorStyle,
+    shuffler: Option<Rc<RefCell<Shuffler>>>,
+    /// Fonts to choose from, ordered from small to large
+    fonts: Vec<Rc<Font>>,
+    font: Rc<Font>,
 }
 
 impl State {
-    fn new(mode: Mode, colorscheme: Colorscheme) -> Self {
+    fn new(
+        mode: Mode,
+        colorscheme: Colorscheme,
+        motion: Motion,
+        seperator_style: SeparatorStyle,
+        shuffler: Option<Shuffler>,
+        fonts: Vec<Rc<Font>>,
+    ) -> Self {
         Self {
             digit_boards: vec![],
             current_digits: vec![],
             seperator_boards: vec![],
+            games: vec![],
+            background_game: false,
+            chrome: None,
+            show_chrome: false,
+            ghost: false,
+            scene: Scene::new(1, 1),
+            scale: 1,
+            block_style: BlockStyle::default(),
+            background: Background::default(),
+            effects: vec![],
+            color_mode: ColorMode::default(),
+            painter: Painter::default(),
+            urgency: None,
+            terminal: None,
+            play: None,
             last_update_time: Local::now(),
             mode,
             colorscheme,
+            schedule: None,
+            colorschemes: vec![],
+            fixed_background: false,
+            notice: None,
+            motion,
+            seperator_style,
+            shuffler: shuffler.map(|shuffler| Rc::new(RefCell::new(shuffler))),
+            font: fonts[0].clone(),
+            fonts,
         }
     }
 
     fn resize_canvas(&mut self, width: u32, height: u32) {
-        // Each digit is 6x10
-        // Spacing 2 between each
-        // There are 6 digits
-        // -> height: 10
-        // -> width: 6*6 + 5*2
-        let x_start = (width as i64 - 6 * 6 - 3 * 2 - 6*2) / 2;
-        let y_stop = (height as i64 + 10) / 2;
-        let now = Local::now();
+        self.background.resize(width, height);
+
+        let width = width / self.scale;
+        let height = height / self.scale;
+        self.scene = Scene::new(width, height);
+        self.painter = Painter::new(self.color_mode(), &self.colorscheme, width, height);
+
+        if let Some(play) = self.play.as_mut() {
+            play.resize(width, height);
+        }
+
+        // Use the largest font, whose digits fit into the canvas. The smallest
+        // one is used, if none of them fits.
+        let chrome_width = if self.show_chrome { chrome::WIDTH } else { 0 };
+        self.font = self
+            .fonts
+            .iter()
+            .rev()
+            .find(|font| {
+                layout_width(font) + chrome_width <= width as i64
+                    && font.height as i64 + 2 <= height as i64
+            })
+            .unwrap_or(&self.fonts[0])
+            .clone();
+
+        // The six digits are grouped in pairs (hours, minutes, seconds). The
+        // digits of a pair are separated by a small gap, while the pairs
+        // themselves are separated by a larger one containing the separators.
+        let digit_width = self.font.width as i64;
+        let digit_height = self.font.height as i64;
+        let x_start = (width as i64 - layout_width(&self.font)) / 2;
+        let y_stop = (height as i64 + digit_height) / 2;
         let digits = time_string_to_digits(self.mode.get_timestring());
 
-        let colorscheme = self.colorscheme.clone();
+        let mut x = x_start;
+        let mut digit_xs = vec![];
+        for i in 0..digits.len() {
+            digit_xs.push(x);
+            x += digit_width
+                + if i % 2 == 0 {
+                    DIGIT_SPACING
+                } else {
+                    SEPERATOR_SPACING
+                };
+        }
+
+        let colorscheme = &self.colorscheme;
         self.digit_boards = digits
             .iter()
             .cloned()
+            .zip(digit_xs.iter())
             .enumerate()
-            .map(|(i, digit)| {
-                // @TODO: This is ugly as hell, but it is late and my brain
-                // doesn't want to come up with something nicer here at the
-                // moment ;)
-                let x = x_start
-                    + match i {
-                        0 => 0,
-                        1 => 6 + 2,
-                        2 => 6 + 2 + 6 + 6,
-                        3 => 6 + 2 + 6 + 6 + 6 + 2,
-                        4 => 6 + 2 + 6 + 6 + 6 + 2 + 6 + 6,
-                        5 => 6 + 2 + 6 + 6 + 6 + 2 + 6 + 6 + 6 + 2,
-                        _ => panic!("unknown digit position {}", i),
-                    };
-                DigitBoard::new(i, x, y_stop, colorscheme, digit.into())
+            .map(|(i, (digit, &x))| {
+                let mut board =
+                    DigitBoard::new(i, x, y_stop, colorscheme.clone(), self.font.clone(), digit);
+                if let Some(shuffler) = &self.shuffler {
+                    board = board.with_shuffler(shuffler.clone());
+                }
+                if self.ghost {
+                    board = board.with_ghost();
+                }
+                board.with_motion(self.motion)
             })
             .collect();
         self.current_digits = digits;
-        self.seperator_boards = vec![
-            // @TODO: This is ugly as hell, but it is late and my brain
-            // doesn't want to come up with something nicer here at the
-            // moment ;)
-            Board::new(x_start + (6 + 2 + 6 + 2), 0, y_stop - 2),
-            Board::new(x_start + (6 + 2 + 6 + 2), -4, y_stop - 6),
-            Board::new(x_start + (6 + 2 + 6 + 6 + 6 + 2 + 6 + 2), 0, y_stop - 2),
-            Board::new(x_start + (6 + 2 + 6 + 6 + 6 + 2 + 6 + 2), -4, y_stop - 6),
-        ];
 
+        // Separators are placed in the middle of the gap following the
+        // second and fourth digit.
         let color = self.colorscheme.apply(Shape::O, Digit::Zero, 0);
-        for board in self.seperator_boards.iter_mut() {
-            board.add_tetromino(0, 0, color, Shape::O, Rotation::NoRotation);
+        self.seperator_boards = [digit_xs[1], digit_xs[3]]
+            .iter()
+            .map(|&x| {
+                SeparatorBoard::new(
+                    x + digit_width + (SEPERATOR_SPACING - 2) / 2,
+                    y_stop,
+                    digit_height,
+                    color,
+                    self.seperator_style,
+                    self.motion,
+                )
+            })
+            .collect();
+
+        self.chrome = self
+            .show_chrome
+            .then(|| Chrome::new(x_start, layout_width(&self.font), y_stop, width));
+
+        // Games are played on both sides of the digits (and the chrome), as
+        // long as there is enough room.
+        self.games = vec![];
+        let margin = (width as i64 - layout_width(&self.font) - chrome_width) / 2 - GAME_SPACING;
+        if self.background_game && margin >= game::WIDTH {
+            let x = (margin - game::WIDTH) / 2;
+            // The last row and column of the canvas are never shown.
+            let game_height = height as i64 - 1;
+            self.games = [x, width as i64 - 1 - x - game::WIDTH]
+                .iter()
+                .map(|&x| Game::new(x, 0, game_height, self.colorscheme.clone()))
+                .collect();
         }
     }
 
@@ -136,8 +358,169 @@ impl State {
                 board.set_digit(digits[i]);
             }
         }
+        if self.current_digits[..4] != digits[..4] {
+            for board in self.seperator_boards.iter_mut() {
+                board.redrop();
+            }
+        }
         self.current_digits = digits;
     }
+
+    /// Switch to another colorscheme together with its background, unless
+    /// another background was chosen. `gradual` changes leave the colors of
+    /// the tetrominos shown, while the others recolor everything right away.
+    fn change_colorscheme(
+        &mut self,
+        colorscheme: Colorscheme,
+        gradual: bool,
+        width: u32,
+        height: u32,
+    ) {
+        if !self.fixed_background {
+            // Backgrounds, which fail to load, are simply kept.
+            if let Ok(background) = colorscheme.background() {
+                self.background = background;
+                self.background.resize(width, height);
+            }
+        }
+        if gradual {
+            self.set_colorscheme(colorscheme);
+        } else {
+            self.recolor(colorscheme);
+        }
+    }
+
+    /// Switch to another colorscheme. Only tetrominos entering from now on
+    /// get the colors of the new colorscheme and the colors of the color
+    /// modes fade over, letting the colors of the clock change gradually.
+    fn set_colorscheme(&mut self, colorscheme: Colorscheme) {
+        for board in self.digit_boards.iter_mut() {
+            board.set_colorscheme(colorscheme.clone());
+        }
+        let color = colorscheme.apply(Shape::O, Digit::Zero, 0);
+        for board in self.seperator_boards.iter_mut() {
+            board.set_color(color);
+        }
+        for game in self.games.iter_mut() {
+            game.set_colorscheme(colorscheme.clone());
+        }
+        self.painter.fade_to_colorscheme(&colorscheme);
+        self.colorscheme = colorscheme;
+    }
+
+    /// Switch to the colorscheme `step` places away in the list of
+    /// colorschemes, recoloring everything shown right away and telling its
+    /// name for a moment. Rules of `--colorscheme auto` stop applying from
+    /// then on. The colors of an urgent countdown are kept.
+    fn cycle_colorscheme(&mut self, step: isize, width: u32, height: u32) {
+        let count = self.colorschemes.len() as isize;
+        if count == 0 || self.is_urgent() {
+            return;
+        }
+        let index = match self
+            .colorschemes
+            .iter()
+            .position(|(_, colorscheme)| *colorscheme == self.colorscheme)
+        {
+            Some(index) => (index as isize + step).rem_euclid(count),
+            None if step > 0 => 0,
+            None => count - 1,
+        };
+        let (name, colorscheme) = self.colorschemes[index as usize].clone();
+        self.change_colorscheme(colorscheme, false, width, height);
+        self.schedule = None;
+        self.notice = Some((name.to_uppercase(), NOTICE_UPDATES));
+    }
+
+    /// Switch to another colorscheme right away, including all tetrominos
+    /// already shown.
+    fn recolor(&mut self, colorscheme: Colorscheme) {
+        for board in self.digit_boards.iter_mut() {
+            board.recolor(colorscheme.clone());
+        }
+        let color = colorscheme.apply(Shape::O, Digit::Zero, 0);
+        for board in self.seperator_boards.iter_mut() {
+            board.recolor(color);
+        }
+        for game in self.games.iter_mut() {
+            game.recolor(colorscheme.clone());
+        }
+        self.painter.set_colorscheme(&colorscheme);
+        self.colorscheme = colorscheme;
+    }
+
+    /// Whether the countdown is about to run out and shows the colors of its
+    /// urgency.
+    fn is_urgent(&self) -> bool {
+        self.urgency
+            .as_ref()
+            .is_some_and(|urgency| urgency.level() != Level::Normal)
+    }
+
+    /// The colors of an urgent countdown are kept, whatever the color mode.
+    fn color_mode(&self) -> ColorMode {
+        if self.is_urgent() {
+            ColorMode::Static
+        } else {
+            self.color_mode
+        }
+    }
+
+    /// Change the colors, once the countdown passes one of the thresholds of
+    /// the urgency. The last minute pulses in red.
+    fn update_urgency(&mut self) {
+        // Without colors only the pulse shows, as dimmed tetrominos are
+        // filled with other characters.
+        let monochrome = self
+            .terminal
+            .as_ref()
+            .is_some_and(|terminal| terminal.is_monochrome());
+        let (Some(urgency), Mode::Countdown(end)) = (&mut self.urgency, &self.mode) else {
+            return;
+        };
+        let colorscheme = urgency.update(end.signed_duration_since(Local::now()));
+        let pulse = urgency.level() == Level::Red && self.mode.is_running();
+        if let Some(colorscheme) = colorscheme.filter(|_| !monochrome) {
+            self.recolor(colorscheme);
+            self.painter.set_mode(self.color_mode());
+        }
+        self.painter.set_pulse(pulse);
+    }
+
+    /// Draw everything onto the scene.
+    fn render_scene(&mut self) {
+        let scene = &mut self.scene;
+        scene.clear();
+
+        if let Some(play) = &self.play {
+            play.render(scene, &format_time_string(self.mode.get_timestring()));
+            return;
+        }
+
+        for game in self.games.iter() {
+            game.render(scene);
+        }
+
+        if let Some(chrome) = &self.chrome {
+            let next = self
+                .digit_boards
+                .iter()
+                .find_map(|board| board.next_tetromino());
+            chrome.render(scene, &self.current_digits, next);
+        }
+
+        for board in self.digit_boards.iter() {
+            board.render(scene, &self.painter);
+        }
+
+        for board in self.seperator_boards.iter() {
+            board.render(scene, &self.painter);
+        }
+
+        if let Some((notice, _)) = &self.notice {
+            text::draw_text(scene, 1, 1, notice, &Color::from_rgb(200, 200, 200));
+        }
+    }
 }
 #[derive(Parser, Debug)]
 #[command(
@@ -153,12 +536,289 @@ struct Args {
         short = 'd',
         long,
         group = "mode",
-        help = "Show a countdown (Duration in HHMMSS or HH:MM:SS)",
+        help = "Show a countdown (Duration in HHMMSS or HH:MM:SS). Press P to play Tetris while waiting",
         value_name = "DURATION"
     )]
     countdown: Option<String>,
-    #[arg(short='s', long, value_enum, default_value_t = Colorscheme::default(), help = "Select a specific colorscheme")]
-    colorscheme: Colorscheme,
+    #[arg(
+        long,
+        requires = "countdown",
+        help = "Turn the digits amber and then red, as the countdown runs out"
+    )]
+    urgency: bool,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "00:05:00",
+        value_parser = parse_duration,
+        help = "Remaining time of the countdown, from which on the digits are amber"
+    )]
+    amber_at: Duration,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "00:01:00",
+        value_parser = parse_duration,
+        help = "Remaining time of the countdown, from which on the digits are red and pulse"
+    )]
+    red_at: Duration,
+    #[arg(
+        short = 's',
+        long,
+        value_name = "COLORSCHEME",
+        default_value = "original",
+        value_parser = ColorschemeNameParser,
+        help = "Select a specific colorscheme, built-in or from the config file"
+    )]
+    colorscheme: String,
+    #[arg(
+        long,
+        value_name = "THEME",
+        conflicts_with = "colorscheme",
+        help = "Take the colors from a terminal theme (base16 .yaml, Alacritty .toml, iTerm2 .itermcolors or Xresources)"
+    )]
+    colorscheme_from: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "List the built-in colorschemes and the ones from the config file"
+    )]
+    list_colorschemes: bool,
+    #[arg(
+        long,
+        help = "Show the contrast of the colors of every colorscheme against its background"
+    )]
+    check_contrast: bool,
+    #[arg(
+        short = 'r',
+        long,
+        help = "Show digits fully assembled instead of letting tetrominos fall in"
+    )]
+    reduced_motion: bool,
+    #[arg(
+        long,
+        requires = "reduced_motion",
+        help = "Fade in digits shown with --reduced-motion"
+    )]
+    fade: bool,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "STYLE",
+        help = "Behaviour of the separators in every mode, unless given for the mode itself"
+    )]
+    separator: Option<SeparatorStyle>,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "STYLE",
+        hide_possible_values = true,
+        help = "Behaviour of the separators of the clock, see --separator [default: static]"
+    )]
+    clock_separator: Option<SeparatorStyle>,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "STYLE",
+        hide_possible_values = true,
+        help = "Behaviour of the separators of the countdown, see --separator [default: blink]"
+    )]
+    countdown_separator: Option<SeparatorStyle>,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "STYLE",
+        hide_possible_values = true,
+        help = "Behaviour of the separators of the stopwatch, see --separator [default: blink]"
+    )]
+    stopwatch_separator: Option<SeparatorStyle>,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "VARIETY",
+        num_args = 0..=1,
+        default_missing_value = "order",
+        help = "Assemble digits differently every time they are shown"
+    )]
+    shuffle: Option<Shuffle>,
+    #[arg(
+        long,
+        requires = "shuffle",
+        help = "Seed for --shuffle, to get the same sequence of animations every time"
+    )]
+    seed: Option<u64>,
+    #[arg(
+        long,
+        help = "Let the computer play Tetris beside the clock, if the terminal is wide enough"
+    )]
+    background_game: bool,
+    #[arg(
+        long,
+        help = "Put the digits into a Tetris well, next to the next tetromino and a score, level and lines panel"
+    )]
+    chrome: bool,
+    #[arg(
+        long,
+        help = "Show where the falling tetromino of a digit is going to land"
+    )]
+    ghost: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..=8),
+        help = "Draw every cell of a tetromino as N x N pixels [default: 1, or 2 with --block-style]"
+    )]
+    scale: Option<u32>,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "STYLE",
+        default_value_t = BlockStyle::default(),
+        help = "Look of the cells of the tetrominos, if they are larger than a pixel"
+    )]
+    block_style: BlockStyle,
+    #[arg(
+        long,
+        value_name = "BACKGROUND",
+        help = "Color (#102030), vertical gradient (#102030:#000000) or PNG image shown behind the digits [default: depends on the colorscheme]"
+    )]
+    background: Option<String>,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "EFFECTS",
+        value_delimiter = ',',
+        help = "Effects applied to the whole picture, separated by commas"
+    )]
+    effects: Vec<Effect>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ColorMode::default(),
+        help = "How the tetrominos of the clock are colored"
+    )]
+    color_mode: ColorMode,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "DEPTH",
+        default_value_t = ColorDepth::default(),
+        help = "Colors supported by the terminal"
+    )]
+    colors: ColorDepth,
+    #[arg(
+        long,
+        help = "Mix colors missing from the palette of the terminal out of neighbouring ones"
+    )]
+    dither: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "Load the glyphs from a font file instead of using the built-in font"
+    )]
+    font: Option<PathBuf>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = DigitSize::default(),
+        conflicts_with = "font",
+        help = "Size of the digits"
+    )]
+    digit_size: DigitSize,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Read the defaults of all options from this file instead of ~/.config/tetrotime/config.toml"
+    )]
+    config: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Use the options of a profile of the config file"
+    )]
+    profile: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[command(about = "Find falling tetrominos, which assemble a glyph bitmap")]
+    Solve {
+        #[arg(help = "Text file with the bitmap of the glyph ('#' filled, '.' empty)")]
+        bitmap: PathBuf,
+        #[arg(long, default_value = "?", help = "Name of the glyph in the output")]
+        name: String,
+    },
+    #[command(about = "Check that every glyph animation of the font assembles its bitmap")]
+    ValidateFont,
+    #[command(
+        about = "Interactively edit the glyph animations of a font file",
+        long_about = "Interactively edit the glyph animations of a font file.\n\n\
+            A file, which does not exist yet, starts out as a copy of the \
+            current font (see --font), or as an empty font with --size.\n\n\
+            Keys: Tab/0-9 select glyph, S shape, R/Up rotate, Left/Right \
+            column, Space/Enter/Down drop, U/Backspace undo, X clear, \
+            P replay, B use the assembled glyph as bitmap, W save, Q/Esc quit"
+    )]
+    EditFont {
+        #[arg(help = "Font file to edit")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "WIDTHxHEIGHT",
+            value_parser = parse_font_size,
+            help = "Start a new font with empty glyphs of the given size"
+        )]
+        size: Option<(usize, usize)>,
+    },
+}
+
+/// Accepts the name of any colorscheme, as the ones from the config file are
+/// only known after parsing the arguments. The built-in ones are shown in the
+/// help.
+#[derive(Clone)]
+struct ColorschemeNameParser;
+
+impl TypedValueParser for ColorschemeNameParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<String, clap::Error> {
+        StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            BuiltinColorscheme::value_variants()
+                .iter()
+                .filter_map(|colorscheme| colorscheme.to_possible_value())
+                .chain(std::iter::once(PossibleValue::new(schedule::AUTO).help(
+                    "Change the colorscheme by the date and time of the day",
+                ))),
+        ))
+    }
+}
+
+fn parse_font_size(size: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("'{}' is not a size like 6x10", size);
+    let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+    match (width.parse(), height.parse()) {
+        (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a duration given in HH:MM:SS or HHMMSS.
+fn parse_duration(duration: &str) -> Result<Duration, String> {
+    NaiveTime::parse_from_str(duration, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(duration, "%H%M%S"))
+        .map(|time| Duration::seconds(time.num_seconds_from_midnight() as i64))
+        .map_err(|_| format!("'{}' is not a duration like 00:05:00 (HH:MM:SS)", duration))
 }
 
 fn get_mode_from_args(args: &Args) -> Result<Mode> {
@@ -167,27 +827,426 @@ fn get_mode_from_args(args: &Args) -> Result<Mode> {
     } else if args.stopwatch {
         Ok(Mode::Stopwatch(Local::now()))
     } else if let Some(countdown) = &args.countdown {
-        let time = NaiveTime::parse_from_str(countdown, "%H:%M:%S").unwrap_or_else(|_| {
-            NaiveTime::parse_from_str(countdown, "%H%M%S")
-                .unwrap_or(NaiveTime::from_num_seconds_from_midnight_opt(0, 0).unwrap())
-        });
-        Ok(Mode::Countdown(
-            Local::now() + Duration::seconds(time.num_seconds_from_midnight() as i64),
-        ))
+        let duration = parse_duration(countdown).unwrap_or_else(|_| Duration::zero());
+        Ok(Mode::Countdown(Local::now() + duration))
     } else {
         Ok(Mode::default())
     }
 }
 
+/// Style of the separators in the given mode. Styles given for the mode
+/// itself take precedence over the one given for all modes.
+fn get_separator_style_from_args(args: &Args, mode: &Mode) -> SeparatorStyle {
+    let (style, default) = match mode {
+        Mode::Clock => (args.clock_separator, SeparatorStyle::Static),
+        Mode::Countdown(_) => (args.countdown_separator, SeparatorStyle::Blink),
+        Mode::Stopwatch(_) => (args.stopwatch_separator, SeparatorStyle::Blink),
+    };
+    style.or(args.separator).unwrap_or(default)
+}
+
+fn get_motion_from_args(args: &Args) -> Motion {
+    match (args.reduced_motion, args.fade) {
+        (true, true) => Motion::Fade,
+        (true, false) => Motion::Instant,
+        _ => Motion::Falling,
+    }
+}
+
+fn solve_bitmap(path: &Path, name: &str) -> Result<()> {
+    let bitmap = Bitmap::parse(std::fs::read_to_string(path)?)?;
+    let animation = solver::solve(&bitmap).ok_or_else(|| {
+        anyhow!(
+            "Could not find tetrominos assembling the bitmap in {}",
+            path.display()
+        )
+    })?;
+
+    let glyph = Glyph {
+        name: name.to_string(),
+        bitmap: Some(bitmap),
+        tetrominos: animation,
+    };
+    print!("{}", glyph);
+
+    Ok(())
+}
+
+fn validate_font(font: &Font) -> Result<()> {
+    println!("Font '{}' ({}x{})", font.name, font.width, font.height);
+
+    let mut failed = 0;
+    for glyph in font.glyphs.iter() {
+        let validation = font.validate(glyph);
+        if validation.problems.is_empty() {
+            println!("{}: ok", glyph.name);
+            continue;
+        }
+
+        failed += 1;
+        println!("{}: {} problem(s)", glyph.name, validation.problems.len());
+        for problem in validation.problems.iter() {
+            println!("  {}", problem);
+        }
+        if let Some(bitmap) = &glyph.bitmap {
+            println!("Expected:\n{}", bitmap);
+        }
+        println!("Assembled:\n{}", validation.result);
+    }
+
+    if failed > 0 {
+        return Err(anyhow!("{} glyph(s) do not assemble correctly", failed));
+    }
+
+    Ok(())
+}
+
+fn edit_font(file: &Path, size: Option<(usize, usize)>, font: &Font) -> Result<Font> {
+    let name = file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match size {
+        Some(_) if file.exists() => Err(anyhow!(
+            "Font file {} already exists, --size only applies to new fonts",
+            file.display()
+        )),
+        Some((width, height)) => Ok(Font::empty(&name, width, height)),
+        None if file.exists() => Font::load(file),
+        None => Ok(Font {
+            name,
+            ..font.clone()
+        }),
+    }
+}
+
+/// Options, which select the config file and therefore cannot be set in it
+const CONFIG_OPTIONS: [&str; 2] = ["config", "profile"];
+
+/// Parse the command line, using the options of the config file as defaults.
+/// Options given on the command line replace the ones of the config file,
+/// as well as options of the config file conflicting with them.
+fn parse_args() -> Result<(Args, Config)> {
+    parse_args_from(std::env::args_os().collect())
+}
+
+/// Same as [parse_args] for the given command line, starting with the name
+/// of the program.
+fn parse_args_from(command_line: Vec<OsString>) -> Result<(Args, Config)> {
+    let mut command = Args::command();
+    command.build();
+    // Only the options selecting the config file are needed here. Whether
+    // options require or conflict with others is checked, once the ones of
+    // the config file are added.
+    let matches = command
+        .clone()
+        .ignore_errors(true)
+        .get_matches_from(command_line.iter());
+    let config = match matches.get_one::<PathBuf>("config") {
+        Some(path) => Config::load_file(path)?,
+        None => Config::load()?,
+    };
+    let options = config.options(matches.get_one::<String>("profile").map(String::as_str))?;
+
+    // Arguments of every option of the config file, together with where it
+    // is found in the config file.
+    let mut from_config = vec![];
+    for (name, value) in options.iter() {
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(name) && !CONFIG_OPTIONS.contains(&name.as_str()))
+            .ok_or_else(|| config.error(value.span(), format!("Unknown option '{}'", name)))?;
+        if is_replaced(&command, &matches, arg) {
+            continue;
+        }
+        let arguments = option_arguments(arg, value.get_ref())
+            .map_err(|message| config.error(value.span(), message))?;
+        from_config.push((value.span(), arguments));
+    }
+
+    let parse = |from_config: &[(std::ops::Range<usize>, Vec<OsString>)]| {
+        let arguments = command_line
+            .iter()
+            .take(1)
+            .chain(from_config.iter().flat_map(|(_, arguments)| arguments))
+            .chain(command_line.iter().skip(1));
+        Args::try_parse_from(arguments)
+    };
+    match parse(&from_config) {
+        Ok(args) => {
+            validate_args(&args)?;
+            Ok((args, config))
+        }
+        // Tell about options of the config file, which require or conflict
+        // with others, by the first one the arguments are accepted without.
+        Err(error)
+            if matches!(
+                error.kind(),
+                ErrorKind::ArgumentConflict | ErrorKind::MissingRequiredArgument
+            ) =>
+        {
+            for i in 0..from_config.len() {
+                let mut others = from_config.clone();
+                let (span, _) = others.remove(i);
+                if parse(&others).is_ok() {
+                    return Err(config.error(span, clap_message(&error)));
+                }
+            }
+            error.exit()
+        }
+        Err(error) => error.exit(),
+    }
+}
+
+/// Check the arguments depending on each other in ways clap does not know
+/// about.
+fn validate_args(args: &Args) -> Result<()> {
+    if args.red_at >= args.amber_at {
+        return Err(anyhow!("--red-at needs to be less than --amber-at"));
+    }
+    Ok(())
+}
+
+/// Message of a clap error without the usage and the hints following it.
+fn clap_message(error: &clap::Error) -> String {
+    let message = error.render().to_string();
+    message
+        .lines()
+        .take_while(|line| !line.trim().is_empty())
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_start_matches("error: ")
+        .to_string()
+}
+
+/// Whether the option or an option it conflicts with (like another mode) is
+/// given on the command line.
+fn is_replaced(command: &clap::Command, matches: &ArgMatches, arg: &clap::Arg) -> bool {
+    let given = |id: &clap::Id| {
+        matches!(
+            matches.value_source(id.as_str()),
+            Some(ValueSource::CommandLine)
+        )
+    };
+    let in_group = command
+        .get_groups()
+        // Groups of alternatives like the modes, not the group of all arguments
+        .filter(|&group| !group.clone().is_multiple())
+        .filter(|group| group.get_args().any(|id| id == arg.get_id()))
+        .flat_map(|group| group.get_args())
+        .any(given);
+    let conflicting = command
+        .get_arg_conflicts_with(arg)
+        .iter()
+        .any(|other| given(other.get_id()));
+    given(arg.get_id()) || in_group || conflicting
+}
+
+/// Turn the value of an option in the config file into command line
+/// arguments, checking that the option accepts it. Flags are set by `true`,
+/// options taking several values accept arrays.
+fn option_arguments(arg: &clap::Arg, value: &toml::Value) -> Result<Vec<OsString>, String> {
+    let option = format!("--{}", arg.get_long().unwrap_or_default());
+    let takes_value = arg.get_num_args().is_some_and(|num| num.takes_values());
+    let values: Vec<String> = match value {
+        toml::Value::Boolean(set)
+            if !takes_value || arg.get_num_args().unwrap().min_values() == 0 =>
+        {
+            return Ok(if *set { vec![option.into()] } else { vec![] });
+        }
+        toml::Value::Boolean(_) => {
+            return Err(format!("{} needs a value, not true or false", option))
+        }
+        _ if !takes_value => {
+            return Err(format!("{} is a flag, set it with true or false", option))
+        }
+        toml::Value::String(text) => vec![text.clone()],
+        toml::Value::Integer(number) => vec![number.to_string()],
+        toml::Value::Float(number) => vec![number.to_string()],
+        toml::Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                toml::Value::String(text) => Ok(text.clone()),
+                toml::Value::Integer(number) => Ok(number.to_string()),
+                _ => Err(format!("{} only accepts strings and numbers", option)),
+            })
+            .collect::<Result<_, _>>()?,
+        _ => return Err(format!("{} does not accept a table or date", option)),
+    };
+
+    // Check the values right away, to tell where they are found in the
+    // config file. Whether the option requires or conflicts with others is
+    // only checked together with the command line.
+    let arguments: Vec<OsString> = values
+        .iter()
+        .map(|value| format!("{}={}", option, value).into())
+        .collect();
+    clap::Command::new("tetrotime")
+        .no_binary_name(true)
+        .arg(
+            arg.clone()
+                .requires(clap::builder::Resettable::Reset)
+                .conflicts_with(clap::builder::Resettable::Reset),
+        )
+        .try_get_matches_from(arguments.iter())
+        .map_err(|error| clap_message(&error))?;
+    Ok(arguments)
+}
+
+/// Print the contrast report of all colorschemes, or only of the one given by
+/// `--colorscheme-from`. A background given with `--background` replaces the
+/// ones of the colorschemes.
+fn check_contrast(args: &Args, config: &Config) -> Result<()> {
+    let colorschemes = match &args.colorscheme_from {
+        Some(theme) => vec![(
+            theme.display().to_string(),
+            Colorscheme::Custom(Rc::new(theme::import(theme)?)),
+        )],
+        None => colorscheme::list(&config.colorschemes)
+            .into_iter()
+            .filter(|(name, _)| name != schedule::AUTO)
+            .map(|(name, _)| {
+                let colorscheme = Colorscheme::find(&name, &config.colorschemes)?;
+                Ok((name, colorscheme))
+            })
+            .collect::<Result<_>>()?,
+    };
+    let background = args
+        .background
+        .as_deref()
+        .map(Background::parse)
+        .transpose()?;
+
+    for (name, colorscheme) in colorschemes {
+        let background = match &background {
+            Some(background) => background,
+            None => &colorscheme.background()?,
+        };
+        contrast::report(&name, &colorscheme, background);
+    }
+    Ok(())
+}
+
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let (args, config) = parse_args()?;
+
+    if args.list_colorschemes {
+        for (name, description) in colorscheme::list(&config.colorschemes) {
+            println!("{:<12} {}", name, description);
+        }
+        return Ok(());
+    }
+    if args.check_contrast {
+        return check_contrast(&args, &config);
+    }
+    // Without colors the shapes are told apart by the original colors, so
+    // anything changing the colors is left out.
+    let depth = args.colors.detect();
+    let schedule = if args.colorscheme == schedule::AUTO
+        && args.colorscheme_from.is_none()
+        && !depth.is_monochrome()
+    {
+        Some(Schedule::new(&config)?)
+    } else {
+        None
+    };
+    let colorscheme = match (&args.colorscheme_from, &schedule) {
+        _ if depth.is_monochrome() => Colorscheme::Builtin(BuiltinColorscheme::Original),
+        (Some(theme), _) => Colorscheme::Custom(Rc::new(theme::import(theme)?)),
+        (None, Some(schedule)) => schedule.pick(&Local::now()),
+        (None, None) => Colorscheme::find(&args.colorscheme, &config.colorschemes)?,
+    };
+
+    let fonts: Vec<Rc<Font>> = match &args.font {
+        Some(path) => vec![Font::load(path)?],
+        None => Font::builtin_sized(args.digit_size),
+    }
+    .into_iter()
+    .map(Rc::new)
+    .collect();
+
+    match &args.command {
+        Some(Command::Solve { bitmap, name }) => return solve_bitmap(bitmap, name),
+        Some(Command::ValidateFont) => {
+            let results: Vec<Result<()>> = fonts.iter().map(|font| validate_font(font)).collect();
+            return results.into_iter().collect();
+        }
+        Some(Command::EditFont { file, size }) => {
+            // Without a specific size new fonts start out as a copy of the
+            // default font.
+            let base = if fonts.len() > 1 {
+                Font::builtin()
+            } else {
+                (*fonts[0]).clone()
+            };
+            let font = edit_font(file, *size, &base)?;
+            editor::run(font, file.clone(), colorscheme)
+        }
+        None => {}
+    }
 
     let mode = get_mode_from_args(&args)?;
 
     let canvas = CrosstermCanvas::new();
     let input = CrosstermInputState::new();
 
-    let mut state = State::new(mode, args.colorscheme);
+    let seperator_style = get_separator_style_from_args(&args, &mode);
+    let mut state = State::new(
+        mode,
+        colorscheme.clone(),
+        get_motion_from_args(&args),
+        seperator_style,
+        args.shuffle
+            .map(|shuffle| Shuffler::new(shuffle, args.seed)),
+        fonts,
+    );
+    state.schedule = schedule;
+    state.colorschemes = colorscheme::list(&config.colorschemes)
+        .into_iter()
+        .filter(|(name, _)| name != schedule::AUTO)
+        .map(|(name, _)| {
+            let colorscheme = Colorscheme::find(&name, &config.colorschemes)?;
+            Ok((name, colorscheme))
+        })
+        .collect::<Result<_>>()?;
+    state.fixed_background = args.background.is_some();
+    state.background_game = args.background_game;
+    state.show_chrome = args.chrome;
+    state.ghost = args.ghost;
+    state.block_style = args.block_style;
+    if let Some(background) = &args.background {
+        state.background = Background::parse(background)?;
+    } else {
+        state.background = colorscheme.background()?;
+    }
+    state.effects = args.effects.clone();
+    state.color_mode = args.color_mode;
+    if depth != ColorDepth::Truecolor {
+        state.terminal = Some(Terminal::new(depth, args.dither));
+    }
+    if args.urgency {
+        state.urgency = Some(Urgency::new(args.amber_at, args.red_at));
+    }
+    // Block styles need cells of more than a single pixel to show.
+    state.scale = args.scale.unwrap_or(match args.block_style {
+        BlockStyle::Flat => 1,
+        _ => 2,
+    });
+    if depth.is_monochrome() {
+        state.colorschemes = vec![];
+        state.color_mode = ColorMode::Static;
+        // Every cell of the scene is written as two characters for each line
+        // it covers, which is about as wide as a character is high.
+        state.scale = match args.scale {
+            Some(scale) if scale % 2 == 1 => {
+                return Err(anyhow!("--scale needs to be an even number without colors"))
+            }
+            Some(scale) => scale,
+            None => 2,
+        };
+    }
     state.resize_canvas(canvas.width(), canvas.height());
 
     eprintln!("Render size: {}x{}", canvas.width(), canvas.height());
@@ -197,45 +1256,223 @@ fn main() -> Result<()> {
         state,
         input,
         canvas,
-        |e, s, input, canvas| {
+        |_, s, input, canvas| {
             if let Some((width, height)) = canvas.did_resize() {
                 s.resize_canvas(width, height);
             }
 
+            if let Some(play) = s.play.as_mut() {
+                if play.handle_input(input) || !s.mode.is_playable() {
+                    // The digits fall in again, showing the current time.
+                    s.play = None;
+                    s.resize_canvas(canvas.width(), canvas.height());
+                } else {
+                    play.update();
+                }
+                return Ok(NextLoopState::Continue);
+            }
+
             if input.is_key_pressed(KeyboardKey::Q) {
                 return Ok(NextLoopState::Exit(0));
             }
 
+            if input.is_key_pressed(KeyboardKey::P) && s.mode.is_playable() {
+                s.play = Some(Play::new(
+                    s.scene.width(),
+                    s.scene.height(),
+                    s.colorscheme.clone(),
+                ));
+                return Ok(NextLoopState::Continue);
+            }
+
+            // Crossterm reports C and Shift+C as the same key, therefore X
+            // cycles backwards.
+            if input.is_key_pressed(KeyboardKey::C) {
+                s.cycle_colorscheme(1, canvas.width(), canvas.height());
+            }
+            if input.is_key_pressed(KeyboardKey::X) {
+                s.cycle_colorscheme(-1, canvas.width(), canvas.height());
+            }
+            if let Some((_, updates)) = s.notice.as_mut() {
+                *updates -= 1;
+                if *updates == 0 {
+                    s.notice = None;
+                }
+            }
+
+            s.update_urgency();
             for board in s.digit_boards.iter_mut() {
-                board.update(canvas);
+                board.update(s.scene.height());
             }
 
-            for board in s.seperator_boards.iter_mut() {
-                board.update(canvas);
+            for game in s.games.iter_mut() {
+                game.update();
             }
 
             let now = Local::now();
+            let second = s.mode.second_fraction();
+            let running = s.mode.is_running();
+            for board in s.seperator_boards.iter_mut() {
+                board.update(s.scene.height(), second, running);
+            }
+
             if now.signed_duration_since(s.last_update_time) > Duration::seconds(5) {
                 s.update_time(time_string_to_digits(s.mode.get_timestring()));
                 s.last_update_time = now;
+
+                // The colors of an urgent countdown are kept.
+                let colorscheme = s.schedule.as_ref().map(|schedule| schedule.pick(&now));
+                if let Some(colorscheme) = colorscheme {
+                    if colorscheme != s.colorscheme && !s.is_urgent() {
+                        s.change_colorscheme(colorscheme, true, canvas.width(), canvas.height());
+                    }
+                }
             }
 
             Ok(NextLoopState::Continue)
         },
-        |e, s, i, canvas, dt| {
-            canvas.clear_screen(&Color::from_rgb(0, 0, 0));
+        |_, s, _, canvas, _| {
+            s.painter
+                .set_time(Local::now().timestamp_millis() as f64 / 1000.0);
+            s.render_scene();
 
-            for board in s.digit_boards.iter() {
-                board.render(canvas);
+            if let Some(terminal) = s
+                .terminal
+                .as_mut()
+                .filter(|terminal| terminal.is_monochrome())
+            {
+                terminal.render_cells(&s.scene, s.scale)?;
+                return Ok(NextLoopState::Continue);
             }
 
-            for board in s.seperator_boards.iter() {
-                board.render(canvas);
-            }
+            s.background.render(canvas);
+            blocks::render_scaled(&s.scene, canvas, s.scale, s.block_style);
+            effects::apply(&s.effects, &s.scene, s.scale, canvas);
 
-            canvas.render()?;
+            match s.terminal.as_mut() {
+                Some(terminal) => terminal.render(canvas)?,
+                None => canvas.render()?,
+            }
 
             Ok(NextLoopState::Continue)
         },
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::TempConfig;
+
+    fn separator_style(args: &[&str], mode: &Mode) -> SeparatorStyle {
+        let args = Args::parse_from(std::iter::once("tetrotime").chain(args.iter().copied()));
+        get_separator_style_from_args(&args, mode)
+    }
+
+    #[test]
+    fn picks_the_separator_style_of_the_mode() {
+        let stopwatch = Mode::Stopwatch(Local::now());
+        assert_eq!(separator_style(&[], &Mode::Clock), SeparatorStyle::Static);
+        assert_eq!(separator_style(&[], &stopwatch), SeparatorStyle::Blink);
+
+        let args = ["--separator", "pulse", "--stopwatch-separator", "redrop"];
+        assert_eq!(separator_style(&args, &Mode::Clock), SeparatorStyle::Pulse);
+        assert_eq!(separator_style(&args, &stopwatch), SeparatorStyle::Redrop);
+    }
+
+    /// Parse the given command line together with a config file of the
+    /// given content.
+    fn parse_with_config(content: &str, args: &[&str]) -> Result<Args> {
+        let config = TempConfig::new(content);
+        let command_line = ["tetrotime", "--config", config.path.to_str().unwrap()]
+            .iter()
+            .chain(args)
+            .map(OsString::from)
+            .collect();
+        parse_args_from(command_line).map(|(args, _)| args)
+    }
+
+    const CONFIG: &str = "scale = 3\n\
+        ghost = true\n\
+        \n\
+        [profiles.standup]\n\
+        countdown = \"00:15:00\"\n\
+        scale = 4\n";
+
+    #[test]
+    fn selects_profiles_of_the_config_file() {
+        let args = parse_with_config(CONFIG, &[]).unwrap();
+        assert_eq!(args.scale, Some(3));
+        assert!(args.ghost);
+        assert!(args.countdown.is_none());
+
+        let args = parse_with_config(CONFIG, &["--profile", "standup"]).unwrap();
+        assert_eq!(args.scale, Some(4));
+        assert!(args.ghost);
+        assert_eq!(args.countdown.as_deref(), Some("00:15:00"));
+
+        let error = parse_with_config(CONFIG, &["--profile", "talk"]);
+        assert!(error
+            .unwrap_err()
+            .to_string()
+            .contains("Unknown profile 'talk'"));
+    }
+
+    #[test]
+    fn prefers_the_command_line_over_the_config_file() {
+        let args = ["--profile", "standup", "--scale", "2", "--clock"];
+        let args = parse_with_config(CONFIG, &args).unwrap();
+        assert_eq!(args.scale, Some(2));
+        // The countdown of the profile conflicts with the clock.
+        assert!(args.clock);
+        assert!(args.countdown.is_none());
+    }
+
+    #[test]
+    fn tells_the_line_of_options_requiring_others() {
+        let config = "scale = 2\nfade = true\n";
+        let error = parse_with_config(config, &[]).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("line 2"), "{}", message);
+        assert!(message.contains("--reduced-motion"), "{}", message);
+
+        let args = parse_with_config(config, &["--reduced-motion"]).unwrap();
+        assert!(args.fade);
+    }
+
+    #[test]
+    fn accepts_flags_requiring_options_of_the_config_file() {
+        let args = parse_with_config("countdown = \"00:10:00\"\n", &["--urgency"]).unwrap();
+        assert!(args.urgency);
+        assert_eq!(args.countdown.as_deref(), Some("00:10:00"));
+
+        let args = parse_with_config("reduced-motion = true\n", &["--fade"]).unwrap();
+        assert!(args.reduced_motion && args.fade);
+    }
+
+    #[test]
+    fn rejects_turning_red_before_amber() {
+        let args = [
+            "--countdown",
+            "00:10:00",
+            "--urgency",
+            "--red-at",
+            "00:05:00",
+        ];
+        assert!(parse_with_config("", &args).is_err());
+
+        let args = ["--amber-at", "00:08:00", "--red-at", "00:05:00"];
+        assert!(parse_with_config("", &args).is_ok());
+    }
+
+    #[test]
+    fn blinks_along_with_the_stopwatch() {
+        let stopwatch = Mode::Stopwatch(Local::now() - Duration::milliseconds(2250));
+        let second = stopwatch.second_fraction();
+        assert!((0.25..0.5).contains(&second), "{}", second);
+
+        let countdown = Mode::Countdown(Local::now() + Duration::milliseconds(2750));
+        let second = countdown.second_fraction();
+        assert!((0.25..0.5).contains(&second), "{}", second);
+    }
+}
@@ -0,0 +1,207 @@
+use clap::ValueEnum;
+use pixel_loop::color::Color;
+
+use crate::colorscheme::Colorscheme;
+use crate::tetromino::dim;
+
+/// Seconds the rainbow needs to pass through all hues once
+const RAINBOW_PERIOD: f64 = 30.0;
+
+/// Saturation of the rainbow colors
+const RAINBOW_SATURATION: f64 = 0.75;
+
+/// Brightness of pulsing tetrominos at the dimmest point of a second
+const PULSE_MIN_BRIGHTNESS: f64 = 0.35;
+
+/// Seconds the colors of the color modes take to fade into the ones of
+/// another colorscheme
+const FADE_SECONDS: f64 = 20.0;
+
+/// How the tetrominos of the clock are colored.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum ColorMode {
+    #[default]
+    #[value(help = "Colors of the colorscheme")]
+    Static,
+    #[value(help = "Gradient through the colors of the colorscheme from left to right")]
+    Horizontal,
+    #[value(help = "Gradient through the colors of the colorscheme from top to bottom")]
+    Vertical,
+    #[value(help = "Rainbow across the display, slowly cycling through all hues")]
+    Rainbow,
+    #[value(help = "Colors of the colorscheme in the order the tetrominos drop in")]
+    Drop,
+}
+
+/// Picks the colors of tetrominos while drawing them, as the color modes
+/// depend on where a tetromino is and on the time, not only on its shape and
+/// digit.
+#[derive(Debug, Clone, Default)]
+pub struct Painter {
+    mode: ColorMode,
+    /// Colors of the colorscheme, which gradients pass through
+    palette: Vec<Color>,
+    /// Palette of the previous colorscheme, which is faded out, together
+    /// with the time the fade started at
+    fading: Option<(Vec<Color>, f64)>,
+    width: i64,
+    height: i64,
+    /// Seconds used to cycle the rainbow and to pulse
+    time: f64,
+    /// Fade the tetrominos in and out every second
+    pulse: bool,
+}
+
+impl Painter {
+    /// Painter for a scene of the given size.
+    pub fn new(mode: ColorMode, colorscheme: &Colorscheme, width: u32, height: u32) -> Self {
+        Self {
+            mode,
+            palette: colorscheme.palette(),
+            fading: None,
+            width: width as i64,
+            height: height as i64,
+            time: 0.0,
+            pulse: false,
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: ColorMode) {
+        self.mode = mode;
+    }
+
+    /// Switch to the colors of another colorscheme right away.
+    pub fn set_colorscheme(&mut self, colorscheme: &Colorscheme) {
+        self.palette = colorscheme.palette();
+        self.fading = None;
+    }
+
+    /// Fade from the current colors into the ones of another colorscheme
+    /// over a few seconds.
+    pub fn fade_to_colorscheme(&mut self, colorscheme: &Colorscheme) {
+        let palette = std::mem::replace(&mut self.palette, colorscheme.palette());
+        self.fading = Some((palette, self.time));
+    }
+
+    pub fn set_time(&mut self, seconds: f64) {
+        self.time = seconds;
+    }
+
+    pub fn set_pulse(&mut self, pulse: bool) {
+        self.pulse = pulse;
+    }
+
+    /// Color of the tetromino at the given position of the scene, which was
+    /// the `order`th one to enter its board. `color` is the one given by the
+    /// colorscheme.
+    pub fn paint(&self, color: Color, x: i64, y: i64, order: usize) -> Color {
+        let mut painted = self.pick(&self.palette, color, x, y, order);
+        if let Some((palette, start)) = &self.fading {
+            let progress = (self.time - start) / FADE_SECONDS;
+            if (0.0..1.0).contains(&progress) {
+                let previous = self.pick(palette, color, x, y, order);
+                painted = mix(&previous, &painted, progress);
+            }
+        }
+
+        if self.pulse {
+            let wave = 0.5 + 0.5 * (self.time * std::f64::consts::TAU).cos();
+            dim(
+                &painted,
+                PULSE_MIN_BRIGHTNESS + (1.0 - PULSE_MIN_BRIGHTNESS) * wave,
+            )
+        } else {
+            painted
+        }
+    }
+
+    /// Color of the color mode using the colors of the given palette.
+    fn pick(&self, palette: &[Color], color: Color, x: i64, y: i64, order: usize) -> Color {
+        let fraction = |value: i64, size: i64| value as f64 / (size - 1).max(1) as f64;
+        match self.mode {
+            ColorMode::Static => color,
+            _ if palette.is_empty() => color,
+            ColorMode::Horizontal => gradient(palette, fraction(x, self.width)),
+            ColorMode::Vertical => gradient(palette, fraction(y, self.height)),
+            ColorMode::Rainbow => {
+                let hue = self.time / RAINBOW_PERIOD + fraction(x, self.width);
+                hsv(hue.rem_euclid(1.0), RAINBOW_SATURATION, 1.0)
+            }
+            ColorMode::Drop => palette[order % palette.len()],
+        }
+    }
+}
+
+/// Color at the given fraction (0.0 to 1.0) of a gradient passing through
+/// the palette.
+fn gradient(palette: &[Color], fraction: f64) -> Color {
+    let position = fraction.clamp(0.0, 1.0) * (palette.len() - 1) as f64;
+    let index = (position as usize).min(palette.len().saturating_sub(2));
+    let from = palette[index];
+    let to = palette[(index + 1).min(palette.len() - 1)];
+    mix(&from, &to, position - index as f64)
+}
+
+/// Color the given fraction (0.0 to 1.0) of the way from one color to
+/// another.
+fn mix(from: &Color, to: &Color, fraction: f64) -> Color {
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * fraction).round() as u8;
+    Color::from_rgb(mix(from.r, to.r), mix(from.g, to.g), mix(from.b, to.b))
+}
+
+/// Color of the given hue (0.0 to 1.0), saturation and value.
+fn hsv(hue: f64, saturation: f64, value: f64) -> Color {
+    let sector = hue * 6.0;
+    let f = sector.fract();
+    let p = value * (1.0 - saturation);
+    let q = value * (1.0 - saturation * f);
+    let t = value * (1.0 - saturation * (1.0 - f));
+    let (r, g, b) = match sector as usize % 6 {
+        0 => (value, t, p),
+        1 => (q, value, p),
+        2 => (p, value, t),
+        3 => (p, q, value),
+        4 => (t, p, value),
+        _ => (value, p, q),
+    };
+    let channel = |channel: f64| (channel * 255.0).round() as u8;
+    Color::from_rgb(channel(r), channel(g), channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::colorscheme::CustomColorscheme;
+
+    fn colorscheme(r: u8, g: u8, b: u8) -> Colorscheme {
+        let colors = [Color::from_rgb(r, g, b); 7];
+        Colorscheme::Custom(Rc::new(CustomColorscheme::from_shapes(colors, None)))
+    }
+
+    #[test]
+    fn fades_into_another_colorscheme() {
+        let black = Color::from_rgb(0, 0, 0);
+        let mut painter = Painter::new(ColorMode::Drop, &colorscheme(0, 0, 200), 10, 10);
+        painter.set_time(100.0);
+        painter.fade_to_colorscheme(&colorscheme(200, 0, 0));
+        assert_eq!(painter.paint(black, 0, 0, 0), Color::from_rgb(0, 0, 200));
+
+        painter.set_time(100.0 + FADE_SECONDS / 2.0);
+        assert_eq!(painter.paint(black, 0, 0, 0), Color::from_rgb(100, 0, 100));
+
+        painter.set_time(100.0 + FADE_SECONDS);
+        assert_eq!(painter.paint(black, 0, 0, 0), Color::from_rgb(200, 0, 0));
+    }
+
+    #[test]
+    fn switches_colorschemes_right_away() {
+        let black = Color::from_rgb(0, 0, 0);
+        let mut painter = Painter::new(ColorMode::Horizontal, &colorscheme(0, 0, 200), 10, 10);
+        painter.fade_to_colorscheme(&colorscheme(0, 200, 0));
+        painter.set_colorscheme(&colorscheme(200, 0, 0));
+        assert_eq!(painter.paint(black, 5, 0, 0), Color::from_rgb(200, 0, 0));
+    }
+}
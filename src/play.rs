@@ -0,0 +1,125 @@
+use pixel_loop::color::Color;
+use pixel_loop::input::{KeyboardKey, KeyboardState};
+
+use crate::colorscheme::Colorscheme;
+use crate::game::{self, Game};
+use crate::scene::CellCanvas;
+use crate::text::{draw_text, text_width, CHAR_HEIGHT};
+
+/// Vertical distance between two lines of text
+const LINE_HEIGHT: i64 = CHAR_HEIGHT + 2;
+
+/// Height of the header showing the remaining time above the well
+const HEADER_HEIGHT: i64 = LINE_HEIGHT + 1;
+
+/// A game of Tetris to pass the time, while a countdown is running.
+pub struct Play {
+    game: Game,
+    width: i64,
+    x: i64,
+    floor: i64,
+}
+
+impl Play {
+    pub fn new(width: u32, height: u32, colorscheme: Colorscheme) -> Self {
+        let (x, floor) = layout(width, height);
+        Self {
+            game: Game::new(x, HEADER_HEIGHT, floor - HEADER_HEIGHT, colorscheme).with_player(),
+            width: width as i64,
+            x,
+            floor,
+        }
+    }
+
+    /// Fit the well into a canvas of another size, keeping the game going.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let (x, floor) = layout(width, height);
+        self.game.move_to(x, HEADER_HEIGHT, floor - HEADER_HEIGHT);
+        self.width = width as i64;
+        self.x = x;
+        self.floor = floor;
+    }
+
+    /// Act upon the pressed keys. Returns true, once the player wants to get
+    /// back to the countdown.
+    pub fn handle_input<I: KeyboardState>(&mut self, input: &I) -> bool {
+        if input.is_key_pressed(KeyboardKey::P)
+            || input.is_key_pressed(KeyboardKey::Q)
+            || input.is_key_pressed(KeyboardKey::Escape)
+        {
+            return true;
+        }
+
+        if input.is_key_pressed(KeyboardKey::Left) {
+            self.game.shift(-1);
+        }
+        if input.is_key_pressed(KeyboardKey::Right) {
+            self.game.shift(1);
+        }
+        if input.is_key_pressed(KeyboardKey::Up) {
+            self.game.rotate();
+        }
+        if input.is_key_pressed(KeyboardKey::Down) {
+            self.game.soft_drop();
+        }
+        if input.is_key_pressed(KeyboardKey::Space) || input.is_key_pressed(KeyboardKey::Enter) {
+            self.game.hard_drop();
+        }
+
+        false
+    }
+
+    pub fn update(&mut self) {
+        self.game.update();
+    }
+
+    /// Draw the well with the remaining time of the countdown above it.
+    pub fn render<C: CellCanvas>(&self, canvas: &mut C, remaining: &str) {
+        let wall = Color::from_rgb(80, 80, 80);
+        let text = Color::from_rgb(200, 200, 200);
+
+        let wall_height = (self.floor - HEADER_HEIGHT + 1) as u32;
+        canvas.filled_rect(self.x - 1, HEADER_HEIGHT, 1, wall_height, &wall);
+        canvas.filled_rect(self.x + game::WIDTH, HEADER_HEIGHT, 1, wall_height, &wall);
+        canvas.filled_rect(self.x - 1, self.floor, game::WIDTH as u32 + 2, 1, &wall);
+        self.game.render(canvas);
+
+        // The header covers the tetromino entering the well.
+        canvas.clear_rect(0, 0, self.width as u32, HEADER_HEIGHT as u32);
+        draw_text(
+            canvas,
+            (self.width - text_width(remaining)) / 2,
+            1,
+            remaining,
+            &text,
+        );
+
+        let lines = [
+            "SCORE".to_string(),
+            self.game.score().to_string(),
+            "LINES".to_string(),
+            self.game.lines().to_string(),
+            "LEVEL".to_string(),
+            (self.game.level() + 1).to_string(),
+        ];
+        let x = self.x + game::WIDTH + 3;
+        for (i, line) in lines.iter().enumerate() {
+            let color = if i % 2 == 0 { &wall } else { &text };
+            draw_text(
+                canvas,
+                x,
+                HEADER_HEIGHT + i as i64 * LINE_HEIGHT,
+                line,
+                color,
+            );
+        }
+    }
+}
+
+/// Horizontal position of the well and vertical position of its floor in a
+/// canvas of the given size.
+fn layout(width: u32, height: u32) -> (i64, i64) {
+    // The last row of the canvas is never shown, the floor of the well is
+    // right above it.
+    ((width as i64 - game::WIDTH) / 2, height as i64 - 2)
+}
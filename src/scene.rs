@@ -0,0 +1,128 @@
+use std::ops::Range;
+
+use pixel_loop::canvas::{Canvas, CrosstermCanvas};
+use pixel_loop::color::Color;
+
+use crate::tetromino::Shape;
+
+/// A tetromino drawn onto a scene. Every tetromino gets an id of its own,
+/// which tells apart neighbouring tetrominos of the same shape and color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Piece {
+    pub id: usize,
+    pub shape: Shape,
+}
+
+/// What covers a cell of the scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cell {
+    /// Nothing, the background shows through
+    Empty,
+    /// Anything but a tetromino, like walls and text
+    Other,
+    Piece(Piece),
+}
+
+/// A canvas, which is told what the pixels drawn onto it belong to.
+/// Canvases only showing colors ignore it.
+pub trait CellCanvas: Canvas {
+    /// Pixels drawn from now on belong to a new tetromino of the given shape,
+    /// or to no tetromino at all.
+    fn set_piece(&mut self, _shape: Option<Shape>) {}
+
+    /// Empty a rectangle of the canvas again.
+    fn clear_rect(&mut self, x: i64, y: i64, width: u32, height: u32) {
+        self.filled_rect(x, y, width, height, &Color::from_rgb(0, 0, 0));
+    }
+}
+
+impl CellCanvas for CrosstermCanvas {}
+
+/// Everything is drawn onto the scene first, which is scaled up to the size
+/// of the canvas afterwards. Every pixel of the scene is one cell of a
+/// tetromino.
+///
+/// Besides the color of every cell, the scene keeps track of what covers it.
+/// Block styles use that to draw edges only where a tetromino ends, while
+/// output without colors picks the characters of a cell by its shape.
+pub struct Scene {
+    width: u32,
+    height: u32,
+    colors: Vec<Color>,
+    cells: Vec<Cell>,
+    /// What pixels drawn right now belong to
+    pen: Cell,
+    pieces: usize,
+}
+
+impl Scene {
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = (width * height) as usize;
+        Self {
+            width,
+            height,
+            colors: vec![Color::from_rgb(0, 0, 0); size],
+            cells: vec![Cell::Empty; size],
+            pen: Cell::Other,
+            pieces: 0,
+        }
+    }
+
+    /// Empty all cells of the scene.
+    pub fn clear(&mut self) {
+        self.colors.fill(Color::from_rgb(0, 0, 0));
+        self.cells.fill(Cell::Empty);
+        self.pen = Cell::Other;
+        self.pieces = 0;
+    }
+
+    /// What covers the cell at the given position. Cells outside of the
+    /// scene are empty.
+    pub fn cell(&self, x: i64, y: i64) -> Cell {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return Cell::Empty;
+        }
+        self.cells[(y * self.width as i64 + x) as usize]
+    }
+}
+
+impl Canvas for Scene {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn set_range(&mut self, range: Range<usize>, colors: &[Color]) {
+        self.cells[range.clone()].fill(self.pen);
+        self.colors[range].copy_from_slice(colors);
+    }
+
+    fn get_range(&self, range: Range<usize>) -> &[Color] {
+        &self.colors[range]
+    }
+}
+
+impl CellCanvas for Scene {
+    fn set_piece(&mut self, shape: Option<Shape>) {
+        self.pen = match shape {
+            Some(shape) => {
+                self.pieces += 1;
+                Cell::Piece(Piece {
+                    id: self.pieces,
+                    shape,
+                })
+            }
+            None => Cell::Other,
+        };
+    }
+
+    fn clear_rect(&mut self, x: i64, y: i64, width: u32, height: u32) {
+        let pen = self.pen;
+        self.pen = Cell::Empty;
+        self.filled_rect(x, y, width, height, &Color::from_rgb(0, 0, 0));
+        self.pen = pen;
+    }
+}
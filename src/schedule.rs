@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Local, Timelike};
+use serde::Deserialize;
+
+use crate::colorscheme::{Colorscheme, ColorschemeConfig};
+use crate::config::Config;
+
+/// Name of the colorscheme changing by the rules of the schedule
+pub const AUTO: &str = "auto";
+
+/// Rule of the schedule in the config file. A rule without dates or hours
+/// always applies.
+///
+/// ```toml
+/// [[auto-colorscheme]]
+/// colorscheme = "christmas"
+/// dates = ["12-01", "12-26"]
+///
+/// [[auto-colorscheme]]
+/// colorscheme = "night"
+/// hours = [22, 6]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    pub colorscheme: String,
+    /// First and last day of the rule as month and day, like `"12-24"`. The
+    /// rule continues into the next year, if the last day comes before the
+    /// first one.
+    pub dates: Option<[String; 2]>,
+    /// Hour the rule starts at and hour it ends at. The rule continues past
+    /// midnight, if it ends before it starts.
+    pub hours: Option<[u32; 2]>,
+}
+
+/// Rules used without any rules in the config file: christmas in december,
+/// dimmed colors at night and the original colors during the day.
+fn default_rules() -> Vec<RuleConfig> {
+    vec![
+        RuleConfig {
+            colorscheme: "christmas".to_string(),
+            dates: Some(["12-01".to_string(), "12-31".to_string()]),
+            hours: None,
+        },
+        RuleConfig {
+            colorscheme: "night".to_string(),
+            dates: None,
+            hours: Some([22, 7]),
+        },
+        RuleConfig {
+            colorscheme: "original".to_string(),
+            dates: None,
+            hours: None,
+        },
+    ]
+}
+
+/// Whether the value is in the range from `first` up to `end`. The range wraps
+/// around, if it ends before it starts, like at the end of a year or at
+/// midnight.
+fn within<T: PartialOrd>(value: T, first: T, end: T) -> bool {
+    if first <= end {
+        first <= value && value < end
+    } else {
+        value >= first || value < end
+    }
+}
+
+struct Rule {
+    colorscheme: Colorscheme,
+    /// First and last day as month and day
+    dates: Option<[(u32, u32); 2]>,
+    hours: Option<[u32; 2]>,
+}
+
+impl Rule {
+    fn new(
+        config: &RuleConfig,
+        colorschemes: &BTreeMap<String, ColorschemeConfig>,
+    ) -> Result<Self> {
+        let date = |date: &String| -> Result<(u32, u32)> {
+            let invalid = || anyhow!("'{}' is not a date like 12-24 (month and day)", date);
+            let (month, day) = date.split_once('-').ok_or_else(invalid)?;
+            match (month.parse(), day.parse()) {
+                (Ok(month @ 1..=12), Ok(day @ 1..=31)) => Ok((month, day)),
+                _ => Err(invalid()),
+            }
+        };
+        let dates = match &config.dates {
+            Some([first, last]) => Some([date(first)?, date(last)?]),
+            None => None,
+        };
+        if let Some(hours) = config.hours {
+            if hours.iter().any(|&hour| hour > 24) {
+                return Err(anyhow!("Hours need to be between 0 and 24"));
+            }
+        }
+
+        Ok(Self {
+            colorscheme: Colorscheme::find(&config.colorscheme, colorschemes)?,
+            dates,
+            hours: config.hours,
+        })
+    }
+
+    fn applies(&self, now: &DateTime<Local>) -> bool {
+        let date_matches = self.dates.is_none_or(|[first, (month, day)]| {
+            // The last day is part of the rule.
+            within((now.month(), now.day()), first, (month, day + 1))
+        });
+        let hour_matches = self
+            .hours
+            .is_none_or(|[first, end]| first == end || within(now.hour(), first, end));
+        date_matches && hour_matches
+    }
+}
+
+/// Colorscheme picked by the date and the time of the day (`--colorscheme
+/// auto`). The first rule applying to the current moment is used.
+pub struct Schedule {
+    rules: Vec<Rule>,
+}
+
+impl Schedule {
+    pub fn new(config: &Config) -> Result<Self> {
+        let rules = if config.auto_colorscheme.is_empty() {
+            default_rules()
+                .iter()
+                .map(|rule| Rule::new(rule, &config.colorschemes))
+                .collect::<Result<_>>()?
+        } else {
+            config
+                .auto_colorscheme
+                .iter()
+                .map(|rule| {
+                    Rule::new(rule.get_ref(), &config.colorschemes)
+                        .map_err(|error| config.error(rule.span(), format!("{:#}", error)))
+                })
+                .collect::<Result<_>>()?
+        };
+        Ok(Self { rules })
+    }
+
+    /// Colorscheme for the given moment. Without any rule applying the
+    /// default colorscheme is used.
+    pub fn pick(&self, now: &DateTime<Local>) -> Colorscheme {
+        self.rules
+            .iter()
+            .find(|rule| rule.applies(now))
+            .map(|rule| rule.colorscheme.clone())
+            .unwrap_or_default()
+    }
+}
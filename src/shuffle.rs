@@ -0,0 +1,170 @@
+use clap::ValueEnum;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::animation::Animation;
+use crate::font::Glyph;
+use crate::simulation::{settle, validate};
+use crate::solver::solve_random;
+use crate::tetromino::Placement;
+
+/// Upper bound of tetromino placements tried, while looking for a random
+/// tiling of a glyph. Digits change while the clock is running, therefore the
+/// search needs to be quick. The tetrominos of the font are used, if no tiling
+/// was found in time.
+const TILING_STEPS: usize = 20_000;
+
+/// Number of random drop orders tried, before falling back to the order given
+/// by the font.
+const ATTEMPTS: usize = 10;
+
+/// Controls how the animation of a digit varies every time it is shown.
+#[derive(Debug, Copy, Clone, PartialEq, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum Shuffle {
+    /// Drop the tetrominos of the font in a random order
+    Order,
+    /// Assemble digits from a random set of tetrominos in a random order
+    /// (needs bitmaps in the font)
+    Tiling,
+}
+
+/// Picks random animations, which assemble exactly the same glyph as the one
+/// given by the font.
+pub struct Shuffler {
+    shuffle: Shuffle,
+    rng: StdRng,
+}
+
+impl Shuffler {
+    /// Create a shuffler, which always produces the same sequence of
+    /// animations for the same seed. Without a seed the sequence is different
+    /// every time.
+    pub fn new(shuffle: Shuffle, seed: Option<u64>) -> Self {
+        Self {
+            shuffle,
+            rng: match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+        }
+    }
+
+    pub fn animation(&mut self, glyph: &Glyph) -> Animation {
+        if self.shuffle == Shuffle::Tiling {
+            if let Some(bitmap) = &glyph.bitmap {
+                if let Some(animation) = solve_random(bitmap, &mut self.rng, TILING_STEPS) {
+                    if validate(&animation, bitmap).problems.is_empty() {
+                        return animation;
+                    }
+                }
+            }
+        }
+
+        self.reorder(&glyph.tetrominos)
+    }
+
+    /// Drop the tetrominos of the animation in a random order, while keeping
+    /// the place every one of them comes to rest at.
+    fn reorder(&mut self, animation: &Animation) -> Animation {
+        let placements = settle(animation);
+        let cells: Vec<Vec<(i64, i64)>> = placements
+            .iter()
+            .map(|placement| {
+                placement
+                    .shape
+                    .cells(placement.rotation)
+                    .iter()
+                    .map(|(dx, dy)| (placement.x + dx, placement.y + dy))
+                    .collect()
+            })
+            .collect();
+
+        // A tetromino needs to be dropped after all tetrominos below it,
+        // which share a column with it.
+        let below: Vec<Vec<usize>> = (0..cells.len())
+            .map(|i| {
+                (0..cells.len())
+                    .filter(|&j| {
+                        j != i
+                            && cells[i].iter().any(|&(x, y)| {
+                                cells[j]
+                                    .iter()
+                                    .any(|&(other_x, other_y)| other_x == x && other_y > y)
+                            })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for _ in 0..ATTEMPTS {
+            let mut order: Vec<usize> = Vec::with_capacity(cells.len());
+            while order.len() < cells.len() {
+                let candidates: Vec<usize> = (0..cells.len())
+                    .filter(|i| !order.contains(i))
+                    .filter(|i| below[*i].iter().all(|j| order.contains(j)))
+                    .collect();
+                let Some(&next) = candidates.choose(&mut self.rng) else {
+                    break;
+                };
+                order.push(next);
+            }
+
+            let candidate: Animation = order.iter().map(|&i| animation[i]).collect();
+            // Tetrominos, which are still falling, may block each other on
+            // their way down. Only orders, which end up in exactly the same
+            // glyph, are used.
+            if candidate.len() == animation.len() && lands_identical(&candidate, &placements) {
+                return candidate;
+            }
+        }
+
+        animation.clone()
+    }
+}
+
+fn lands_identical(animation: &Animation, placements: &[Placement]) -> bool {
+    let result = settle(animation);
+    result.len() == placements.len()
+        && result
+            .iter()
+            .all(|placement| placements.contains(placement))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{Digit, FallingTetromino};
+    use crate::font::{DigitSize, Font};
+    use crate::tetromino::{Rotation, Shape};
+
+    #[test]
+    fn tells_apart_orders_landing_elsewhere() {
+        let i = FallingTetromino::new(Shape::I, Rotation::Degrees90, 0);
+        let o = FallingTetromino::new(Shape::O, Rotation::NoRotation, 0);
+        let animation = vec![i, o];
+        let placements = settle(&animation);
+
+        assert!(lands_identical(&animation, &placements));
+        // The O lands at the bottom, once dropped first.
+        assert!(!lands_identical(&vec![o, i], &placements));
+        assert!(!lands_identical(&vec![i], &placements));
+    }
+
+    #[test]
+    fn shuffles_into_the_same_glyphs() {
+        for shuffle in [Shuffle::Order, Shuffle::Tiling] {
+            let mut shuffler = Shuffler::new(shuffle, Some(7));
+            for font in Font::builtin_sized(DigitSize::Auto) {
+                for digit in Digit::ALL {
+                    let glyph = font.digit_glyph(digit);
+                    let animation = shuffler.animation(glyph);
+                    let expected = font.validate(glyph).result;
+                    let problems = validate(&animation, &expected).problems;
+                    assert!(problems.is_empty(), "{} of {}", digit, font.name);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt;
+
+use pixel_loop::color::Color;
+
+use crate::animation::{Animation, FallingTetromino};
+use crate::bitmap::Bitmap;
+use crate::tetromino::{Board, Placement, UPDATES_PER_TETROMINO};
+
+/// Drop all tetrominos of an animation onto an empty board, without any
+/// visible output, and report where they came to rest.
+///
+/// The simulation uses the same `Board`, collision and timing of tetrominos
+/// entering the board as a `DigitBoard` on the real display.
+///
+/// The returned placements use the `dx` of the animation as x coordinate,
+/// while y is relative to the bottom of the board (the `y_stop`). A tetromino
+/// resting on the ground therefore has an y coordinate of 0.
+pub fn settle(animation: &Animation) -> Vec<Placement> {
+    let color = Color::from_rgb(255, 255, 255);
+    let y_stop = stack_height(animation);
+    let mut board = Board::new(0, 0, y_stop);
+
+    let mut pending = animation.iter();
+    let mut updates_since_last_tetromino = UPDATES_PER_TETROMINO;
+    loop {
+        let mut added = false;
+        if updates_since_last_tetromino >= UPDATES_PER_TETROMINO {
+            if let Some(&FallingTetromino {
+                shape,
+                rotation,
+                dx,
+            }) = pending.next()
+            {
+                board.add_tetromino(dx, 0, color, shape, rotation);
+                updates_since_last_tetromino = 0;
+                added = true;
+            }
+        }
+
+        let before: Vec<Placement> = board.placements().collect();
+        board.update(y_stop as u32);
+        updates_since_last_tetromino += 1;
+
+        if !added && pending.len() == 0 && board.placements().eq(before) {
+            break;
+        }
+    }
+
+    board
+        .placements()
+        .map(|placement| Placement {
+            y: placement.y - y_stop,
+            ..placement
+        })
+        .collect()
+}
+
+/// Rows covered by all tetrominos of the animation stacked on top of each
+/// other.
+///
+/// Tetrominos enter the simulated board above its first row. A board of this
+/// height therefore leaves room for any glyph the animation could assemble,
+/// without tetrominos entering the board overlapping the ones resting on it.
+fn stack_height(animation: &Animation) -> i64 {
+    animation
+        .iter()
+        .map(|tetromino| {
+            let rows = tetromino.shape.cells(tetromino.rotation).map(|(_, dy)| dy);
+            rows.iter().max().unwrap() - rows.iter().min().unwrap() + 1
+        })
+        .sum()
+}
+
+/// A reason for an animation not assembling its glyph correctly.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// The cell is covered by more than one tetromino
+    Overlap { x: i64, y: i64 },
+    /// The tetromino (index in the animation) does not rest on anything
+    Floating { index: usize },
+    /// The cell should be covered by a tetromino, but is not
+    Missing { x: i64, y: i64 },
+    /// The cell is covered by a tetromino, but should be empty
+    Extra { x: i64, y: i64 },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Overlap { x, y } => {
+                write!(f, "Cell {},{} is covered by multiple tetrominos", x, y)
+            }
+            Problem::Floating { index } => {
+                write!(f, "Tetromino #{} does not rest on anything", index + 1)
+            }
+            Problem::Missing { x, y } => write!(f, "Cell {},{} is not covered", x, y),
+            Problem::Extra { x, y } => {
+                write!(f, "Cell {},{} is covered, but should be empty", x, y)
+            }
+        }
+    }
+}
+
+/// Result of simulating an animation against the bitmap of its glyph.
+pub struct Validation {
+    /// The cells covered once all tetrominos came to rest.
+    ///
+    /// Cells outside of the expected bitmap are not part of it, but still
+    /// reported as [Problem::Extra].
+    pub result: Bitmap,
+    pub problems: Vec<Problem>,
+}
+
+/// Drop all tetrominos of an animation onto an empty board and check that
+/// the covered cells exactly match the given bitmap.
+pub fn validate(animation: &Animation, bitmap: &Bitmap) -> Validation {
+    let height = bitmap.height() as i64;
+    let placements = settle(animation);
+
+    let mut result = Bitmap::new(bitmap.width(), bitmap.height());
+    let mut problems = vec![];
+    let mut covered: Vec<(i64, i64)> = vec![];
+
+    for placement in placements.iter() {
+        for (dx, dy) in placement.shape.cells(placement.rotation) {
+            let (x, y) = (placement.x + dx, height + placement.y + dy);
+            if covered.contains(&(x, y)) {
+                problems.push(Problem::Overlap { x, y });
+            }
+            covered.push((x, y));
+            result.set(x, y, true);
+        }
+    }
+
+    for (index, placement) in placements.iter().enumerate() {
+        let cells: Vec<(i64, i64)> = placement
+            .shape
+            .cells(placement.rotation)
+            .iter()
+            .map(|(dx, dy)| (placement.x + dx, height + placement.y + dy))
+            .collect();
+        let rests = cells.iter().any(|&(x, y)| {
+            !cells.contains(&(x, y + 1)) && (y + 1 == height || covered.contains(&(x, y + 1)))
+        });
+        if !rests {
+            problems.push(Problem::Floating { index });
+        }
+    }
+
+    for &(x, y) in covered.iter() {
+        if !bitmap.contains(x, y) {
+            problems.push(Problem::Extra { x, y });
+        }
+    }
+    for y in 0..height {
+        for x in 0..bitmap.width() as i64 {
+            match (bitmap.get(x, y), result.get(x, y)) {
+                (true, false) => problems.push(Problem::Missing { x, y }),
+                (false, true) => problems.push(Problem::Extra { x, y }),
+                _ => {}
+            }
+        }
+    }
+
+    Validation { result, problems }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::Digit;
+    use crate::font::{DigitSize, Font};
+    use crate::solver::solve;
+    use crate::tetromino::{Rotation, Shape};
+
+    #[test]
+    fn builtin_glyphs_assemble_their_bitmap() {
+        for font in Font::builtin_sized(DigitSize::Auto) {
+            for glyph in font.glyphs.iter() {
+                let validation = font.validate(glyph);
+                assert_eq!(
+                    validation.problems,
+                    vec![],
+                    "Animation of '{}' in font '{}' assembles:\n{}",
+                    glyph.name,
+                    font.name,
+                    validation.result
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn solved_animations_assemble_their_glyph() {
+        for glyph in Font::builtin().glyphs.iter() {
+            let bitmap = glyph.bitmap.as_ref().unwrap();
+            let animation = solve(bitmap).expect("glyph can be solved");
+            let validation = validate(&animation, bitmap);
+            assert_eq!(validation.problems, vec![], "'{}'", glyph.name);
+        }
+    }
+
+    #[test]
+    fn assembles_glyphs_of_any_size() {
+        let tall = vec![FallingTetromino::new(Shape::O, Rotation::NoRotation, 0); 20];
+        let validation = validate(&tall, &Bitmap::filled(2, 40));
+        assert_eq!(validation.problems, vec![]);
+
+        let wide: Animation = (0..10)
+            .map(|i| FallingTetromino::new(Shape::I, Rotation::NoRotation, 4 * i))
+            .collect();
+        let validation = validate(&wide, &Bitmap::filled(40, 1));
+        assert_eq!(validation.problems, vec![]);
+    }
+
+    #[test]
+    fn reports_missing_cells() {
+        let font = Font::builtin();
+        let glyph = font.glyph(&Digit::One.to_string()).unwrap();
+        let mut animation = glyph.tetrominos.clone();
+        animation.pop();
+        let validation = validate(&animation, glyph.bitmap.as_ref().unwrap());
+        assert_eq!(
+            validation.problems,
+            vec![
+                Problem::Missing { x: 4, y: 0 },
+                Problem::Missing { x: 5, y: 0 },
+                Problem::Missing { x: 4, y: 1 },
+                Problem::Missing { x: 5, y: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_cells_outside_of_the_glyph() {
+        let animation = vec![FallingTetromino::new(Shape::I, Rotation::NoRotation, 4)];
+        let validation = validate(&animation, &Bitmap::parse("..##").unwrap());
+        assert_eq!(
+            validation.problems,
+            vec![
+                Problem::Extra { x: 4, y: 0 },
+                Problem::Extra { x: 5, y: 0 },
+                Problem::Extra { x: 6, y: 0 },
+                Problem::Extra { x: 7, y: 0 },
+                Problem::Missing { x: 2, y: 0 },
+                Problem::Missing { x: 3, y: 0 },
+            ]
+        );
+
+        let validation = validate(&animation, &Bitmap::parse("....####").unwrap());
+        assert_eq!(validation.problems, vec![]);
+    }
+
+    #[test]
+    fn reports_tetrominos_landing_elsewhere() {
+        // The second O falls onto the first one, instead of next to it
+        let animation = vec![
+            FallingTetromino::new(Shape::O, Rotation::NoRotation, 0),
+            FallingTetromino::new(Shape::O, Rotation::NoRotation, 1),
+        ];
+        let validation = validate(&animation, &Bitmap::parse("####\n####").unwrap());
+        assert!(validation
+            .problems
+            .contains(&Problem::Missing { x: 3, y: 1 }));
+        assert!(validation
+            .problems
+            .contains(&Problem::Extra { x: 1, y: -1 }));
+    }
+}
@@ -0,0 +1,367 @@
+use std::collections::HashSet;
+
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+use crate::animation::{Animation, FallingTetromino};
+use crate::bitmap::Bitmap;
+use crate::tetromino::{Rotation, Shape};
+
+// Upper bound of tetromino placements tried, before giving up on a bitmap.
+// Some bitmaps can not be tiled at all, which otherwise would only be noticed
+// after exhausting every possible tiling.
+const MAX_STEPS: usize = 2_000_000;
+
+/// A tetromino at its final position within the bitmap.
+///
+/// The coordinate follows the same convention as the tetrominos on a `Board`:
+/// `x` and `y` describe the lower left corner of the shape. The bottom row of
+/// a bitmap with a height of 10 therefore has an y coordinate of 10.
+#[derive(Debug, Copy, Clone)]
+struct Piece {
+    shape: Shape,
+    rotation: Rotation,
+    x: i64,
+    y: i64,
+}
+
+impl Piece {
+    fn cells(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.shape
+            .cells(self.rotation)
+            .into_iter()
+            .map(|(dx, dy)| (self.x + dx, self.y + dy))
+    }
+}
+
+/// Find an animation of falling tetrominos, which assembles the given bitmap.
+///
+/// The bitmap is tiled with tetrominos first. A tiling is only accepted if
+/// there is an order to drop its tetrominos, so that each of them lands
+/// exactly at its place under the gravity rules of a `Board`:
+///
+/// * Every tetromino needs to rest on the ground or on another tetromino, as
+///   it would otherwise continue to fall.
+/// * A tetromino needs to be dropped after all tetrominos below it, sharing a
+///   column with it. It would otherwise block their way down.
+///
+/// Returns `None` if no such tiling could be found.
+pub fn solve(bitmap: &Bitmap) -> Option<Animation> {
+    Solver::new(bitmap, MAX_STEPS, None).run()
+}
+
+/// Find a random animation, which assembles the given bitmap.
+///
+/// Works like [solve], but picks tetrominos for the tiling, as well as the
+/// order to drop them in, at random. Gives up after trying `max_steps`
+/// tetromino placements.
+pub fn solve_random(bitmap: &Bitmap, rng: &mut dyn RngCore, max_steps: usize) -> Option<Animation> {
+    Solver::new(bitmap, max_steps, Some(rng)).run()
+}
+
+struct Solver<'a> {
+    bitmap: &'a Bitmap,
+    /// Index of the piece covering each cell of the bitmap
+    owners: Vec<Option<usize>>,
+    pieces: Vec<Piece>,
+    steps: usize,
+    max_steps: usize,
+    /// Partial tilings, which are known to not lead to a solution
+    failed: HashSet<(i64, i64, Vec<bool>)>,
+    /// Whether partial tilings known to fail are skipped. Only turned off by
+    /// tests, to check that the solutions stay the same.
+    #[cfg(test)]
+    memoize: bool,
+    /// Number of complete tilings without a valid drop order
+    unordered: usize,
+    /// Source for random decisions. Without it the first possible tetromino
+    /// is always chosen.
+    rng: Option<&'a mut dyn RngCore>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(bitmap: &'a Bitmap, max_steps: usize, rng: Option<&'a mut dyn RngCore>) -> Self {
+        Self {
+            bitmap,
+            owners: vec![None; bitmap.width() * bitmap.height()],
+            pieces: vec![],
+            steps: 0,
+            max_steps,
+            failed: HashSet::new(),
+            #[cfg(test)]
+            memoize: true,
+            unordered: 0,
+            rng,
+        }
+    }
+
+    fn run(mut self) -> Option<Animation> {
+        if !self.bitmap.count().is_multiple_of(4) {
+            return None;
+        }
+
+        let order = self.search()?;
+        Some(
+            order
+                .into_iter()
+                .map(|i| {
+                    let Piece {
+                        shape, rotation, x, ..
+                    } = self.pieces[i];
+                    FallingTetromino::new(shape, rotation, x)
+                })
+                .collect(),
+        )
+    }
+
+    #[cfg(not(test))]
+    fn memoize(&self) -> bool {
+        true
+    }
+
+    #[cfg(test)]
+    fn memoize(&self) -> bool {
+        self.memoize
+    }
+
+    fn owner(&self, x: i64, y: i64) -> Option<usize> {
+        if self.bitmap.contains(x, y) {
+            self.owners[y as usize * self.bitmap.width() + x as usize]
+        } else {
+            None
+        }
+    }
+
+    fn set_owner(&mut self, x: i64, y: i64, owner: Option<usize>) {
+        let width = self.bitmap.width();
+        self.owners[y as usize * width + x as usize] = owner;
+    }
+
+    fn is_free(&self, x: i64, y: i64) -> bool {
+        self.bitmap.get(x, y) && self.owner(x, y).is_none()
+    }
+
+    // The bitmap is filled from the bottom up, as this is where tetrominos
+    // need to be placed first anyways.
+    fn next_free_cell(&self) -> Option<(i64, i64)> {
+        let width = self.bitmap.width() as i64;
+        let height = self.bitmap.height() as i64;
+        (0..height)
+            .rev()
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .find(|&(x, y)| self.is_free(x, y))
+    }
+
+    /// Check whether the piece is, or still can be, resting on something.
+    ///
+    /// As the bitmap is filled from the bottom up, a cell below the piece,
+    /// which is not yet covered, will be covered once the tiling is complete.
+    fn can_rest(&self, piece: &Piece) -> bool {
+        let cells: Vec<(i64, i64)> = piece.cells().collect();
+        cells
+            .iter()
+            .filter(|(x, y)| !cells.contains(&(*x, *y + 1)))
+            .any(|&(x, y)| {
+                y + 1 == self.bitmap.height() as i64
+                    || self.owner(x, y + 1).is_some()
+                    || self.is_free(x, y + 1)
+            })
+    }
+
+    /// Describe a partial tiling by the cells covered above the free cell.
+    ///
+    /// As the bitmap is filled from the bottom up, everything below the free
+    /// cell is covered already. Pieces reach at most three rows above it,
+    /// which leaves the cells up to there as the only difference between two
+    /// partial tilings sharing the same free cell.
+    fn frontier(&self, x: i64, y: i64) -> (i64, i64, Vec<bool>) {
+        let width = self.bitmap.width() as i64;
+        let covered = (y - 3..=y)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self.owner(x, y).is_some())
+            .collect();
+        (x, y, covered)
+    }
+
+    fn search(&mut self) -> Option<Vec<usize>> {
+        let Some((x, y)) = self.next_free_cell() else {
+            let order = self.drop_order();
+            if order.is_none() {
+                self.unordered += 1;
+            }
+            return order;
+        };
+
+        let frontier = self.frontier(x, y);
+        if self.memoize() && self.failed.contains(&frontier) {
+            return None;
+        }
+        let unordered = self.unordered;
+
+        let mut candidates: Vec<(Shape, Rotation)> = Shape::ALL
+            .iter()
+            .flat_map(|&shape| {
+                shape
+                    .distinct_rotations()
+                    .iter()
+                    .map(move |&rotation| (shape, rotation))
+            })
+            .collect();
+        if let Some(rng) = self.rng.as_mut() {
+            candidates.shuffle(&mut **rng);
+        }
+
+        for (shape, rotation) in candidates {
+            // All cells below and to the left of the free cell are already
+            // covered. The free cell therefore has to be the lowest (and
+            // then leftmost) cell of the piece.
+            let (dx, dy) = shape
+                .cells(rotation)
+                .into_iter()
+                .max_by_key(|&(dx, dy)| (dy, -dx))
+                .unwrap();
+            let piece = Piece {
+                shape,
+                rotation,
+                x: x - dx,
+                y: y - dy,
+            };
+
+            if !piece.cells().all(|(x, y)| self.is_free(x, y)) || !self.can_rest(&piece) {
+                continue;
+            }
+
+            self.steps += 1;
+            if self.steps > self.max_steps {
+                return None;
+            }
+
+            let index = self.pieces.len();
+            for (x, y) in piece.cells() {
+                self.set_owner(x, y, Some(index));
+            }
+            self.pieces.push(piece);
+
+            if let Some(order) = self.search() {
+                return Some(order);
+            }
+
+            self.pieces.pop();
+            for (x, y) in piece.cells() {
+                self.set_owner(x, y, None);
+            }
+        }
+
+        // Whether a complete tiling can be dropped depends on all of its
+        // pieces, not only on the frontier. Failures caused by the drop order
+        // are therefore not remembered.
+        if self.memoize() && self.unordered == unordered {
+            self.failed.insert(frontier);
+        }
+
+        None
+    }
+
+    /// Order the pieces of a complete tiling, so that every piece is dropped
+    /// after all pieces below it.
+    ///
+    /// Returns `None` if the pieces block each other.
+    fn drop_order(&mut self) -> Option<Vec<usize>> {
+        let count = self.pieces.len();
+        let mut below: Vec<Vec<usize>> = vec![vec![]; count];
+
+        for x in 0..self.bitmap.width() as i64 {
+            let mut previous: Option<usize> = None;
+            for y in (0..self.bitmap.height() as i64).rev() {
+                if let Some(owner) = self.owner(x, y) {
+                    if let Some(previous) = previous.filter(|&previous| previous != owner) {
+                        below[owner].push(previous);
+                    }
+                    previous = Some(owner);
+                }
+            }
+        }
+
+        let mut order = Vec::with_capacity(count);
+        let mut dropped = vec![false; count];
+        while order.len() < count {
+            let candidates =
+                (0..count).filter(|&i| !dropped[i] && below[i].iter().all(|&j| dropped[j]));
+            // Unless picking at random, prefer to drop the lowest and then
+            // leftmost piece, which looks like a natural way of stacking them
+            // up.
+            let next = match self.rng.as_mut() {
+                Some(rng) => *candidates.collect::<Vec<_>>().choose(&mut **rng)?,
+                None => candidates.max_by_key(|&i| (self.pieces[i].y, -self.pieces[i].x))?,
+            };
+            dropped[next] = true;
+            order.push(next);
+        }
+
+        Some(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::Digit;
+    use crate::font::{DigitSize, Font, Glyph};
+
+    /// Digit glyphs of all built-in fonts, which come with a bitmap.
+    fn digit_glyphs() -> Vec<(Font, Glyph)> {
+        Font::builtin_sized(DigitSize::Auto)
+            .into_iter()
+            .flat_map(|font| {
+                Digit::ALL
+                    .iter()
+                    .map(|&digit| (font.clone(), font.digit_glyph(digit).clone()))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|(_, glyph)| glyph.bitmap.is_some())
+            .collect()
+    }
+
+    #[test]
+    fn solves_every_digit() {
+        let glyphs = digit_glyphs();
+        assert!(!glyphs.is_empty());
+        for (font, glyph) in glyphs {
+            let tetrominos = solve(glyph.bitmap.as_ref().unwrap())
+                .unwrap_or_else(|| panic!("{} of {} is not solved", glyph.name, font.name));
+            let validation = font.validate(&Glyph {
+                tetrominos,
+                ..glyph.clone()
+            });
+            assert!(
+                validation.problems.is_empty(),
+                "{} of {}: {:?}",
+                glyph.name,
+                font.name,
+                validation.problems
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_untileable_bitmaps() {
+        // Four cells, which do not touch each other
+        let scattered = Bitmap::parse("#.\n.#\n#.\n.#\n").unwrap();
+        assert!(solve(&scattered).is_none());
+
+        // A cell count, which is no multiple of four
+        let odd = Bitmap::parse("###\n###\n").unwrap();
+        assert!(solve(&odd).is_none());
+    }
+
+    #[test]
+    fn memoizing_keeps_the_solutions() {
+        for (font, glyph) in digit_glyphs() {
+            let bitmap = glyph.bitmap.as_ref().unwrap();
+            let mut solver = Solver::new(bitmap, MAX_STEPS, None);
+            solver.memoize = false;
+            let solution = solver.run();
+            assert_eq!(solve(bitmap), solution, "{} of {}", glyph.name, font.name);
+        }
+    }
+}
@@ -0,0 +1,457 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use pixel_loop::canvas::Canvas;
+use pixel_loop::color::Color;
+
+use crate::animation::Digit;
+use crate::colorscheme::BuiltinColorscheme;
+use crate::scene::{Cell, Scene};
+use crate::tetromino::Shape;
+
+/// Time between two frames, matching the frame limit of the crossterm canvas
+const FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Levels of the red, green and blue channels of the 6x6x6 color cube of the
+/// 256 color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The 16 ANSI colors as shown by xterm. Other terminals use slightly
+/// different shades, which are close enough to find the nearest color.
+const ANSI_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Characters filling a cell without colors: empty cells, the shapes in the
+/// order of `Shape::ALL`, anything gray like text and walls, and dimmed
+/// tetrominos like ghosts
+const BLOCK_FILLS: [&str; 10] = ["  ", "██", "▓▓", "▒▒", "░░", "[]", "##", "%%", "::", ".."];
+const ASCII_FILLS: [&str; 10] = ["  ", "[]", "##", "@@", "%%", "==", "&&", "$$", "::", ".."];
+
+/// Brightness compared to the color of its shape, below which a tetromino
+/// counts as dimmed
+const DIMMED_BRIGHTNESS: f64 = 0.6;
+
+/// Thresholds of ordered dithering with a 4x4 Bayer matrix
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Colors the terminal is able to show.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum ColorDepth {
+    #[default]
+    #[value(help = "Detect the colors of the terminal by COLORTERM and TERM")]
+    Auto,
+    #[value(help = "24-bit colors")]
+    Truecolor,
+    #[value(name = "256", help = "The 256 color palette")]
+    Ansi256,
+    #[value(name = "16", help = "The 16 ANSI colors")]
+    Ansi16,
+    #[value(
+        name = "mono",
+        help = "No colors, shapes are told apart by block characters"
+    )]
+    Monochrome,
+    #[value(help = "No colors and only ASCII characters")]
+    Ascii,
+}
+
+impl ColorDepth {
+    /// Resolve `Auto` by the environment. Terminals announce 24-bit colors
+    /// with `COLORTERM`, while `TERM` tells about the palette. Without any
+    /// hint 24-bit colors are assumed, as they were always used before.
+    /// Colors are left out entirely, if `NO_COLOR` is set or the terminal is
+    /// `dumb`.
+    pub fn detect(self) -> Self {
+        self.detect_by(|name| {
+            std::env::var_os(name).map(|value| value.to_string_lossy().into_owned())
+        })
+    }
+
+    /// Resolve `Auto` by the given lookup of environment variables.
+    fn detect_by(self, var: impl Fn(&str) -> Option<String>) -> Self {
+        if self != ColorDepth::Auto {
+            return self;
+        }
+
+        if var("NO_COLOR").is_some_and(|no_color| !no_color.is_empty()) {
+            return ColorDepth::Monochrome;
+        }
+
+        let colorterm = var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorDepth::Truecolor;
+        }
+        match var("TERM") {
+            Some(term) if term == "dumb" => ColorDepth::Ascii,
+            Some(term) if term.ends_with("-direct") => ColorDepth::Truecolor,
+            Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+            Some(term) if !term.is_empty() => ColorDepth::Ansi16,
+            _ => ColorDepth::Truecolor,
+        }
+    }
+
+    /// Whether the terminal shows characters only, without any colors.
+    pub fn is_monochrome(&self) -> bool {
+        matches!(self, ColorDepth::Monochrome | ColorDepth::Ascii)
+    }
+
+    /// Distance between neighbouring colors of the palette, which dithering
+    /// spreads colors across.
+    fn spacing(&self) -> f64 {
+        match self {
+            ColorDepth::Ansi16 => 128.0,
+            _ => 40.0,
+        }
+    }
+
+    /// Index of the palette color closest to the given color.
+    fn quantize(&self, (r, g, b): (f64, f64, f64)) -> u8 {
+        let distance = |(cr, cg, cb): (u8, u8, u8)| {
+            (r - cr as f64).powi(2) + (g - cg as f64).powi(2) + (b - cb as f64).powi(2)
+        };
+        match self {
+            ColorDepth::Ansi16 => (0..16)
+                .min_by(|&a, &b| {
+                    distance(ANSI_COLORS[a as usize]).total_cmp(&distance(ANSI_COLORS[b as usize]))
+                })
+                .unwrap_or(0),
+            _ => {
+                let level = |channel: f64| {
+                    (0..6)
+                        .min_by(|&a, &b| {
+                            (channel - CUBE_LEVELS[a] as f64)
+                                .abs()
+                                .total_cmp(&(channel - CUBE_LEVELS[b] as f64).abs())
+                        })
+                        .unwrap_or(0)
+                };
+                let (lr, lg, lb) = (level(r), level(g), level(b));
+                let cube = (CUBE_LEVELS[lr], CUBE_LEVELS[lg], CUBE_LEVELS[lb]);
+
+                // The 24 shades of gray between the cube colors
+                let step = (((r + g + b) / 3.0 - 8.0) / 10.0).round().clamp(0.0, 23.0) as u8;
+                let gray = 8 + 10 * step;
+                if distance((gray, gray, gray)) < distance(cube) {
+                    232 + step
+                } else {
+                    16 + 36 * lr as u8 + 6 * lg as u8 + lb as u8
+                }
+            }
+        }
+    }
+}
+
+/// Writes the canvas to terminals without 24-bit colors, showing every pixel
+/// in the closest color of the palette instead. Like the crossterm canvas, two
+/// pixels on top of each other share a character using a half block, and only
+/// changed characters are written. Without any colors the cells of the scene
+/// are written as characters instead.
+pub struct Terminal {
+    depth: ColorDepth,
+    dither: bool,
+    /// Palette colors shown during the last frame
+    previous: Vec<u8>,
+    width: u32,
+    height: u32,
+    last_frame: Instant,
+}
+
+impl Terminal {
+    pub fn new(depth: ColorDepth, dither: bool) -> Self {
+        Self {
+            depth,
+            dither,
+            previous: vec![],
+            width: 0,
+            height: 0,
+            last_frame: Instant::now(),
+        }
+    }
+
+    fn color(&self, color: &Color, x: u32, y: u32) -> u8 {
+        let offset = if self.dither {
+            let threshold = BAYER[y as usize % 4][x as usize % 4] as f64 / 16.0 - 0.5;
+            threshold * self.depth.spacing()
+        } else {
+            0.0
+        };
+        let channel = |channel: u8| channel as f64 + offset;
+        self.depth
+            .quantize((channel(color.r), channel(color.g), channel(color.b)))
+    }
+
+    /// Escape sequence selecting the given palette colors for the upper and
+    /// the lower half of a character.
+    fn colors(&self, upper: u8, lower: u8) -> String {
+        match self.depth {
+            ColorDepth::Ansi16 => {
+                let code = |color: u8, base: u8| {
+                    if color < 8 {
+                        base + color
+                    } else {
+                        base + 60 + color - 8
+                    }
+                };
+                format!("\x1b[{};{}m", code(upper, 30), code(lower, 40))
+            }
+            _ => format!("\x1b[38;5;{};48;5;{}m", upper, lower),
+        }
+    }
+
+    pub fn is_monochrome(&self) -> bool {
+        self.depth.is_monochrome()
+    }
+
+    fn wait_for_next_frame(&mut self) {
+        if let Some(wait) = FRAME_TIME.checked_sub(self.last_frame.elapsed()) {
+            std::thread::sleep(wait);
+        }
+        self.last_frame = Instant::now();
+    }
+
+    pub fn render<C: Canvas>(&mut self, canvas: &C) -> Result<()> {
+        self.wait_for_next_frame();
+
+        let (width, height) = (canvas.width(), canvas.height());
+        let colors: Vec<u8> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self.color(canvas.get(x, y), x, y))
+            .collect();
+        // Everything is written again after the terminal changed its size.
+        if width != self.width || height != self.height {
+            self.previous = vec![];
+            self.width = width;
+            self.height = height;
+        }
+
+        let color = |colors: &[u8], x: u32, y: u32| {
+            if y < height {
+                colors.get((y * width + x) as usize).copied()
+            } else {
+                Some(0)
+            }
+        };
+        let mut output = String::from("\x1b[?25l");
+        for row in (0..height).step_by(2) {
+            // Characters are only positioned at the start of a run of changes.
+            let mut positioned = false;
+            let mut last = None;
+            for x in 0..width {
+                let pair = (color(&colors, x, row), color(&colors, x, row + 1));
+                if pair
+                    == (
+                        color(&self.previous, x, row),
+                        color(&self.previous, x, row + 1),
+                    )
+                {
+                    positioned = false;
+                    continue;
+                }
+                if !positioned {
+                    output.push_str(&format!("\x1b[{};{}H", row / 2 + 1, x + 1));
+                    positioned = true;
+                }
+                let pair = (pair.0.unwrap_or(0), pair.1.unwrap_or(0));
+                if last != Some(pair) {
+                    output.push_str(&self.colors(pair.0, pair.1));
+                    last = Some(pair);
+                }
+                output.push('▀');
+            }
+        }
+        output.push_str(&format!("\x1b[{};{}H\x1b[?25h", height / 2 + 1, width + 1));
+
+        write(&output)?;
+        self.previous = colors;
+        Ok(())
+    }
+
+    /// Write the scene without colors. Every cell of the scene is `scale`
+    /// pixels of the canvas wide and high, which are two characters for each
+    /// row of half as many lines. The scene needs to be colored with the
+    /// original colorscheme, to tell dimmed tetrominos apart.
+    pub fn render_cells(&mut self, scene: &Scene, scale: u32) -> Result<()> {
+        self.wait_for_next_frame();
+
+        let (width, height) = (scene.width(), scene.height());
+        let fills: Vec<u8> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| fill(scene.cell(x as i64, y as i64), scene.get(x, y)))
+            .collect();
+        if width != self.width || height != self.height {
+            self.previous = vec![];
+            self.width = width;
+            self.height = height;
+        }
+
+        let characters = if self.depth == ColorDepth::Ascii {
+            ASCII_FILLS
+        } else {
+            BLOCK_FILLS
+        };
+        let size = (scale / 2).max(1);
+        let mut output = String::from("\x1b[?25l");
+        for y in 0..height {
+            for line in 0..size {
+                let mut positioned = false;
+                for x in 0..width {
+                    let index = (y * width + x) as usize;
+                    if self.previous.get(index) == Some(&fills[index]) {
+                        positioned = false;
+                        continue;
+                    }
+                    if !positioned {
+                        let (row, column) = (y * size + line + 1, 2 * x * size + 1);
+                        output.push_str(&format!("\x1b[{};{}H", row, column));
+                        positioned = true;
+                    }
+                    output.push_str(&characters[fills[index] as usize].repeat(size as usize));
+                }
+            }
+        }
+        output.push_str(&format!("\x1b[{};1H\x1b[?25h", height * size + 1));
+
+        write(&output)?;
+        self.previous = fills;
+        Ok(())
+    }
+}
+
+fn write(output: &str) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(output.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Index of the characters filling a cell. Tetrominos are filled by their
+/// shape, unless they are dimmed compared to the color of their shape in the
+/// original colorscheme.
+fn fill(cell: Cell, color: &Color) -> u8 {
+    let piece = match cell {
+        Cell::Empty => return 0,
+        Cell::Other => return 8,
+        Cell::Piece(piece) => piece,
+    };
+
+    let brightness = |color: &Color| color.r.max(color.g).max(color.b) as f64;
+    let original = BuiltinColorscheme::Original.apply(piece.shape, Digit::Zero, 0);
+    if brightness(color) < DIMMED_BRIGHTNESS * brightness(&original) {
+        return 9;
+    }
+
+    let index = Shape::ALL
+        .iter()
+        .position(|&shape| shape == piece.shape)
+        .expect("every shape is part of Shape::ALL");
+    1 + index as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Piece;
+    use crate::tetromino::dim;
+
+    fn detect(vars: &[(&str, &str)]) -> ColorDepth {
+        ColorDepth::Auto.detect_by(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn detects_colors_by_term_and_colorterm() {
+        assert_eq!(detect(&[("TERM", "dumb")]), ColorDepth::Ascii);
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), ColorDepth::Ansi256);
+        assert_eq!(detect(&[("TERM", "xterm-direct")]), ColorDepth::Truecolor);
+        assert_eq!(detect(&[("TERM", "xterm")]), ColorDepth::Ansi16);
+        assert_eq!(detect(&[]), ColorDepth::Truecolor);
+        assert_eq!(
+            detect(&[("TERM", "xterm"), ("COLORTERM", "truecolor")]),
+            ColorDepth::Truecolor
+        );
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("NO_COLOR", "1")]),
+            ColorDepth::Monochrome
+        );
+        assert_eq!(ColorDepth::Ansi16.detect_by(|_| None), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn quantizes_to_the_256_color_palette() {
+        let depth = ColorDepth::Ansi256;
+        assert_eq!(depth.quantize((0.0, 0.0, 0.0)), 16);
+        assert_eq!(depth.quantize((95.0, 135.0, 255.0)), 16 + 36 + 2 * 6 + 5);
+        assert_eq!(depth.quantize((255.0, 255.0, 255.0)), 231);
+        assert_eq!(depth.quantize((128.0, 128.0, 128.0)), 244);
+        assert_eq!(depth.quantize((8.0, 8.0, 8.0)), 232);
+    }
+
+    #[test]
+    fn quantizes_to_the_16_ansi_colors() {
+        let depth = ColorDepth::Ansi16;
+        for (index, (r, g, b)) in ANSI_COLORS.iter().enumerate() {
+            assert_eq!(
+                depth.quantize((*r as f64, *g as f64, *b as f64)),
+                index as u8
+            );
+        }
+        assert_eq!(depth.quantize((240.0, 10.0, 20.0)), 9);
+    }
+
+    #[test]
+    fn selects_colors_with_escape_sequences() {
+        let terminal = Terminal::new(ColorDepth::Ansi16, false);
+        assert_eq!(terminal.colors(1, 0), "\x1b[31;40m");
+        assert_eq!(terminal.colors(7, 8), "\x1b[37;100m");
+        assert_eq!(terminal.colors(15, 9), "\x1b[97;101m");
+
+        let terminal = Terminal::new(ColorDepth::Ansi256, false);
+        assert_eq!(terminal.colors(196, 232), "\x1b[38;5;196;48;5;232m");
+    }
+
+    fn original(shape: Shape) -> Color {
+        BuiltinColorscheme::Original.apply(shape, Digit::Zero, 0)
+    }
+
+    #[test]
+    fn shapes_have_fills_of_their_own() {
+        let fills: Vec<u8> = Shape::ALL
+            .iter()
+            .map(|&shape| fill(Cell::Piece(Piece { id: 1, shape }), &original(shape)))
+            .collect();
+        assert_eq!(fills, vec![1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn fills_dimmed_tetrominos_and_everything_else() {
+        let piece = Cell::Piece(Piece {
+            id: 1,
+            shape: Shape::T,
+        });
+        assert_eq!(fill(piece, &dim(&original(Shape::T), 0.35)), 9);
+        assert_eq!(fill(Cell::Empty, &Color::from_rgb(0, 0, 0)), 0);
+        assert_eq!(fill(Cell::Other, &Color::from_rgb(80, 80, 80)), 8);
+    }
+}
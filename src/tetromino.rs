@@ -1,172 +1,19 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use clap::ValueEnum;
-use pixel_loop::canvas::Canvas;
 use pixel_loop::color::Color;
+use serde::Deserialize;
 
 use crate::animation::{Animation, Digit, FallingTetromino};
+use crate::colorscheme::Colorscheme;
+use crate::font::Font;
+use crate::paint::Painter;
+use crate::scene::CellCanvas;
+use crate::shuffle::Shuffler;
+use crate::simulation::settle;
 
-#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
-#[clap(rename_all = "lowercase")]
-pub enum Colorscheme {
-    #[value(help = "Classic Tetris colors with bright, distinct shades")]
-    Original,
-    #[value(help = "Different shades of gray for a monochrome look")]
-    Grayscale,
-    #[value(help = "Colors based on the digit position in the time display")]
-    Position,
-    #[value(help = "Each digit (0-9) gets its own distinct color")]
-    Digit,
-    #[value(help = "Vibrant neon colors inspired by cyberpunk aesthetics")]
-    Neon,
-    #[value(help = "Soft, muted pastel colors for a gentle appearance")]
-    Pastel,
-    #[value(help = "Shades of blue and turquoise inspired by ocean depths")]
-    Ocean,
-    #[value(help = "Warm fall colors with reds, browns and golden tones")]
-    Autumn,
-    #[value(help = "Traditional Christmas theme with reds, greens and gold")]
-    Christmas,
-    #[value(help = "Warm spectrum focusing on yellows and oranges")]
-    Warm,
-    #[value(help = "Digital green shades inspired by The Matrix movie")]
-    Matrix,
-    #[value(help = "Rich and deep purple/violet color palette")]
-    Purple,
-}
-
-impl Default for Colorscheme {
-    fn default() -> Self {
-        Self::Original
-    }
-}
-
-impl Colorscheme {
-    pub fn apply(&self, shape: Shape, digit: Digit, position: usize) -> Color {
-        match self {
-            Colorscheme::Original => match shape {
-                Shape::L => Color::from_rgb(223, 155, 42),
-                Shape::J => Color::from_rgb(22, 0, 230),
-                Shape::O => Color::from_rgb(237, 238, 57),
-                Shape::T => Color::from_rgb(136, 26, 231),
-                Shape::I => Color::from_rgb(103, 232, 236),
-                Shape::S => Color::from_rgb(100, 233, 49),
-                Shape::Z => Color::from_rgb(213, 50, 27),
-            },
-            Colorscheme::Grayscale => match shape {
-                Shape::L => Color::from_rgb(178, 178, 178),
-                Shape::J => Color::from_rgb(74, 74, 74),
-                Shape::O => Color::from_rgb(234, 234, 234),
-                Shape::T => Color::from_rgb(98, 98, 98),
-                Shape::I => Color::from_rgb(216, 216, 216),
-                Shape::S => Color::from_rgb(200, 200, 200),
-                Shape::Z => Color::from_rgb(120, 120, 120),
-            },
-            Colorscheme::Position => match position {
-                0 => Color::from_rgb(237, 238, 57),
-                1 => Color::from_rgb(213, 50, 27),
-                2 => Color::from_rgb(223, 155, 42),
-                3 => Color::from_rgb(136, 26, 231),
-                4 => Color::from_rgb(103, 232, 236),
-                5 => Color::from_rgb(100, 233, 49),
-                6 => Color::from_rgb(213, 50, 27),
-                _ => panic!("Unknown position for Colorscheme 'position' {}", position),
-            },
-            Colorscheme::Digit => match digit {
-                Digit::Zero => Color::from_rgb(180, 180, 180),
-                Digit::One => Color::from_rgb(255, 215, 0),
-                Digit::Two => Color::from_rgb(255, 105, 180),
-                Digit::Three => Color::from_rgb(0, 139, 139),
-                Digit::Four => Color::from_rgb(255, 160, 122),
-                Digit::Five => Color::from_rgb(147, 112, 219),
-                Digit::Six => Color::from_rgb(32, 178, 170),
-                Digit::Seven => Color::from_rgb(255, 218, 185),
-                Digit::Eight => Color::from_rgb(176, 196, 222),
-                Digit::Nine => Color::from_rgb(255, 192, 203),
-            },
-            Colorscheme::Neon => match shape {
-                // Bright, glowing neon colors
-                Shape::L => Color::from_rgb(255, 41, 117), // Hot pink
-                Shape::J => Color::from_rgb(0, 255, 255),  // Cyan
-                Shape::O => Color::from_rgb(255, 236, 39), // Neon yellow
-                Shape::T => Color::from_rgb(167, 0, 255),  // Bright purple
-                Shape::I => Color::from_rgb(0, 255, 169),  // Bright mint
-                Shape::S => Color::from_rgb(123, 255, 0),  // Lime green
-                Shape::Z => Color::from_rgb(255, 82, 0),   // Neon orange
-            },
-            Colorscheme::Pastel => match shape {
-                // Soft, muted pastel colors
-                Shape::L => Color::from_rgb(255, 183, 197), // Soft pink
-                Shape::J => Color::from_rgb(174, 198, 255), // Pastel blue
-                Shape::O => Color::from_rgb(255, 251, 150), // Pastel yellow
-                Shape::T => Color::from_rgb(211, 178, 255), // Pastel purple
-                Shape::I => Color::from_rgb(178, 255, 241), // Pastel turquoise
-                Shape::S => Color::from_rgb(178, 255, 178), // Pastel green
-                Shape::Z => Color::from_rgb(255, 178, 178), // Pastel red
-            },
-            Colorscheme::Ocean => match shape {
-                // Colors from shallow to deep ocean
-                Shape::L => Color::from_rgb(144, 224, 239), // Light aqua
-                Shape::J => Color::from_rgb(0, 119, 182),   // Deep blue
-                Shape::O => Color::from_rgb(202, 240, 248), // Sea foam
-                Shape::T => Color::from_rgb(3, 4, 94),      // Deep ocean
-                Shape::I => Color::from_rgb(0, 150, 199),   // Azure
-                Shape::S => Color::from_rgb(72, 202, 228),  // Sky blue
-                Shape::Z => Color::from_rgb(0, 180, 216),   // Turquoise
-            },
-            Colorscheme::Autumn => match shape {
-                // Warm fall colors
-                Shape::L => Color::from_rgb(230, 125, 35), // Burnt orange
-                Shape::J => Color::from_rgb(153, 62, 29),  // Auburn
-                Shape::O => Color::from_rgb(255, 190, 15), // Golden yellow
-                Shape::T => Color::from_rgb(130, 48, 56),  // Wine red
-                Shape::I => Color::from_rgb(217, 80, 48),  // Rust
-                Shape::S => Color::from_rgb(85, 107, 47),  // Olive green
-                Shape::Z => Color::from_rgb(168, 50, 27),  // Maroon
-            },
-            Colorscheme::Christmas => match shape {
-                // Traditional Christmas colors with rich reds, greens, and gold accents
-                Shape::L => Color::from_rgb(176, 0, 0), // Deep Christmas red
-                Shape::J => Color::from_rgb(0, 105, 0), // Forest green
-                Shape::O => Color::from_rgb(255, 215, 0), // Golden yellow
-                Shape::T => Color::from_rgb(146, 22, 34), // Cardinal red
-                Shape::I => Color::from_rgb(0, 140, 0), // Christmas tree green
-                Shape::S => Color::from_rgb(212, 175, 55), // Metallic gold
-                Shape::Z => Color::from_rgb(190, 0, 0), // Bright Christmas red
-            },
-            Colorscheme::Warm => match shape {
-                // Warm colors focusing on yellows and oranges
-                Shape::L => Color::from_rgb(255, 167, 0), // Pure orange
-                Shape::J => Color::from_rgb(255, 140, 0), // Dark orange
-                Shape::O => Color::from_rgb(255, 215, 0), // Golden yellow
-                Shape::T => Color::from_rgb(255, 198, 0), // Amber
-                Shape::I => Color::from_rgb(255, 179, 25), // Marigold
-                Shape::S => Color::from_rgb(255, 126, 0), // Safety orange
-                Shape::Z => Color::from_rgb(255, 103, 0), // Burnt orange
-            },
-            Colorscheme::Matrix => match shape {
-                // Different intensities of the iconic Matrix green
-                Shape::L => Color::from_rgb(0, 255, 0), // Pure Matrix green
-                Shape::J => Color::from_rgb(0, 185, 0), // Medium bright green
-                Shape::O => Color::from_rgb(172, 255, 172), // Light digital green
-                Shape::T => Color::from_rgb(0, 140, 0), // Dark digital green
-                Shape::I => Color::from_rgb(0, 215, 0), // Bright digital green
-                Shape::S => Color::from_rgb(128, 255, 128), // Pale Matrix green
-                Shape::Z => Color::from_rgb(0, 155, 0), // Muted Matrix green
-            },
-            Colorscheme::Purple => match shape {
-                // Rich, deep purples and violet shades
-                Shape::L => Color::from_rgb(148, 0, 211), // Dark violet
-                Shape::J => Color::from_rgb(106, 13, 173), // Deep purple
-                Shape::O => Color::from_rgb(178, 102, 255), // Bright violet
-                Shape::T => Color::from_rgb(75, 0, 130),  // Indigo
-                Shape::I => Color::from_rgb(128, 0, 128), // Pure purple
-                Shape::S => Color::from_rgb(153, 50, 204), // Dark orchid
-                Shape::Z => Color::from_rgb(139, 0, 139), // Deep magenta
-            },
-        }
-    }
-}
-
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
 pub enum Shape {
     L,
     J,
@@ -177,7 +24,64 @@ pub enum Shape {
     Z,
 }
 
-#[derive(Debug, Copy, Clone)]
+impl Shape {
+    pub const ALL: [Shape; 7] = [
+        Shape::L,
+        Shape::J,
+        Shape::O,
+        Shape::T,
+        Shape::I,
+        Shape::S,
+        Shape::Z,
+    ];
+
+    /// Offsets of the four cells of the shape in the given rotation, relative
+    /// to the coordinate of the tetromino (see the explanation at `Tetromino`).
+    ///
+    /// An offset of `(0, -1)` describes the cell directly above and to the
+    /// right of the coordinate, as the coordinate is the lower left corner of
+    /// a cell.
+    pub fn cells(&self, rotation: Rotation) -> [(i64, i64); 4] {
+        use Rotation::*;
+        use Shape::*;
+        match (self, rotation) {
+            (L, NoRotation) => [(0, -2), (0, -1), (1, -2), (2, -2)],
+            (L, Degrees90) => [(0, -3), (0, -2), (0, -1), (-1, -3)],
+            (L, Degrees180) => [(0, -1), (1, -1), (2, -1), (2, -2)],
+            (L, Degrees270) => [(0, -3), (0, -2), (0, -1), (1, -1)],
+            (J, NoRotation) => [(-2, -2), (-1, -2), (0, -2), (0, -1)],
+            (J, Degrees90) => [(0, -1), (1, -1), (1, -3), (1, -2)],
+            (J, Degrees180) => [(0, -2), (0, -1), (1, -1), (2, -1)],
+            (J, Degrees270) => [(0, -3), (0, -2), (0, -1), (1, -3)],
+            (O, _) => [(0, -2), (1, -2), (0, -1), (1, -1)],
+            (T, NoRotation) => [(-1, -2), (0, -2), (1, -2), (0, -1)],
+            (T, Degrees90) => [(0, -3), (0, -2), (0, -1), (-1, -2)],
+            (T, Degrees180) => [(0, -1), (1, -1), (2, -1), (1, -2)],
+            (T, Degrees270) => [(0, -3), (0, -2), (0, -1), (1, -2)],
+            (I, NoRotation) | (I, Degrees180) => [(0, -1), (1, -1), (2, -1), (3, -1)],
+            (I, Degrees90) | (I, Degrees270) => [(0, -4), (0, -3), (0, -2), (0, -1)],
+            (S, NoRotation) | (S, Degrees180) => [(0, -1), (1, -1), (1, -2), (2, -2)],
+            (S, Degrees90) | (S, Degrees270) => [(0, -2), (0, -1), (-1, -3), (-1, -2)],
+            (Z, NoRotation) | (Z, Degrees180) => [(0, -1), (1, -1), (-1, -2), (0, -2)],
+            (Z, Degrees90) | (Z, Degrees270) => [(0, -2), (0, -1), (1, -3), (1, -2)],
+        }
+    }
+
+    /// All rotations of the shape, which result in a distinct set of cells.
+    pub fn distinct_rotations(&self) -> &'static [Rotation] {
+        use Rotation::*;
+        match self {
+            Shape::O => &[NoRotation],
+            Shape::I | Shape::S | Shape::Z => &[NoRotation, Degrees90],
+            Shape::L | Shape::J | Shape::T => &[NoRotation, Degrees90, Degrees180, Degrees270],
+        }
+    }
+}
+
+// Rotations are stored in font files by their angle in degrees.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "u16")]
+#[allow(clippy::enum_variant_names)]
 pub enum Rotation {
     Degrees90,
     Degrees180,
@@ -185,6 +89,60 @@ pub enum Rotation {
     NoRotation,
 }
 
+impl TryFrom<u16> for Rotation {
+    type Error = String;
+
+    fn try_from(degrees: u16) -> Result<Self, Self::Error> {
+        match degrees {
+            0 => Ok(Rotation::NoRotation),
+            90 => Ok(Rotation::Degrees90),
+            180 => Ok(Rotation::Degrees180),
+            270 => Ok(Rotation::Degrees270),
+            _ => Err(format!(
+                "Rotation needs to be one of 0, 90, 180 or 270 degrees. Got {}",
+                degrees
+            )),
+        }
+    }
+}
+
+impl From<Rotation> for u16 {
+    fn from(rotation: Rotation) -> Self {
+        match rotation {
+            Rotation::NoRotation => 0,
+            Rotation::Degrees90 => 90,
+            Rotation::Degrees180 => 180,
+            Rotation::Degrees270 => 270,
+        }
+    }
+}
+
+/// Controls how digits appear on the board when they change.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Motion {
+    /// Tetrominos fall in one after the other (the classic look)
+    #[default]
+    Falling,
+    /// Digits are swapped in fully assembled
+    Instant,
+    /// Digits are swapped in fully assembled and faded in over a few frames
+    Fade,
+}
+
+/// Controls how the separators between hours, minutes and seconds behave.
+#[derive(Debug, Copy, Clone, PartialEq, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum SeparatorStyle {
+    /// Drop in once and stay
+    Static,
+    /// Show the separators only during the first half of every second
+    Blink,
+    /// Fall out and drop in again whenever the minute changes
+    Redrop,
+    /// Fade the color of the separators in and out every second
+    Pulse,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 enum FallState {
     In,
@@ -214,107 +172,126 @@ struct Tetromino {
     x: i64,
     y: i64,
     color: Color,
+    /// Number of tetrominos, which entered the board before this one
+    order: usize,
     fall: FallState,
 }
 
-fn would_tetromino_collide_with_canvas<C: Canvas>(
-    Tetromino {
-        shape,
-        rotation,
-        x,
-        y,
-        ..
-    }: &Tetromino,
-    canvas: &C,
-) -> bool {
-    let empty = Color::from_rgb(0, 0, 0);
+impl Tetromino {
+    /// Cells covered by the tetromino on the canvas
+    fn cells(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.shape
+            .cells(self.rotation)
+            .into_iter()
+            .map(|(dx, dy)| (self.x + dx, self.y + dy))
+    }
+}
+
+fn draw_tetromino<C: CellCanvas>(
+    canvas: &mut C,
+    shape: Shape,
+    rotation: Rotation,
+    x: i64,
+    y: i64,
+    color: &Color,
+) {
     use Rotation::*;
     use Shape::*;
+    canvas.set_piece(Some(shape));
     match (shape, rotation) {
         (L, NoRotation) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y - 1, &empty)
-                || !canvas.is_empty_or_color(*x + 2, *y - 1, &empty)
+            canvas.filled_rect(x, y - 2, 1, 2, color);
+            canvas.filled_rect(x + 1, y - 2, 2, 1, color);
         }
         (L, Degrees90) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x - 1, *y - 2, &empty)
+            canvas.filled_rect(x, y - 3, 1, 3, color);
+            canvas.filled_rect(x - 1, y - 3, 1, 1, color);
         }
         (L, Degrees180) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 2, *y, &empty)
+            canvas.filled_rect(x, y - 1, 3, 1, color);
+            canvas.filled_rect(x + 2, y - 2, 1, 1, color);
         }
         (L, Degrees270) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y, &empty)
+            canvas.filled_rect(x, y - 3, 1, 3, color);
+            canvas.filled_rect(x + 1, y - 1, 1, 1, color);
         }
         (J, NoRotation) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x - 1, *y - 1, &empty)
-                || !canvas.is_empty_or_color(*x - 2, *y - 1, &empty)
+            canvas.filled_rect(x - 2, y - 2, 2, 1, color);
+            canvas.filled_rect(x, y - 2, 1, 2, color);
         }
         (J, Degrees90) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y, &empty)
+            canvas.filled_rect(x, y - 1, 2, 1, color);
+            canvas.filled_rect(x + 1, y - 3, 1, 2, color);
         }
         (J, Degrees180) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 2, *y, &empty)
+            canvas.filled_rect(x, y - 2, 1, 2, color);
+            canvas.filled_rect(x + 1, y - 1, 2, 1, color);
         }
         (J, Degrees270) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y - 2, &empty)
+            canvas.filled_rect(x, y - 3, 1, 3, color);
+            canvas.filled_rect(x + 1, y - 3, 1, 1, color);
         }
         (O, _) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y, &empty)
+            canvas.filled_rect(x, y - 2, 2, 2, color);
         }
         (T, NoRotation) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y - 1, &empty)
-                || !canvas.is_empty_or_color(*x - 1, *y - 1, &empty)
+            canvas.filled_rect(x - 1, y - 2, 3, 1, color);
+            canvas.filled_rect(x, y - 1, 1, 1, color);
         }
         (T, Degrees90) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x - 1, *y - 1, &empty)
+            canvas.filled_rect(x, y - 3, 1, 3, color);
+            canvas.filled_rect(x - 1, y - 2, 1, 1, color);
         }
         (T, Degrees180) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 2, *y, &empty)
+            canvas.filled_rect(x, y - 1, 3, 1, color);
+            canvas.filled_rect(x + 1, y - 2, 1, 1, color);
         }
         (T, Degrees270) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y - 1, &empty)
+            canvas.filled_rect(x, y - 3, 1, 3, color);
+            canvas.filled_rect(x + 1, y - 2, 1, 1, color);
         }
         (I, NoRotation) | (I, Degrees180) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 2, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 3, *y, &empty)
+            canvas.filled_rect(x, y - 1, 4, 1, color);
+        }
+        (I, Degrees90) | (I, Degrees270) => {
+            canvas.filled_rect(x, y - 4, 1, 4, color);
         }
-        (I, Degrees90) | (I, Degrees270) => !canvas.is_empty_or_color(*x, *y, &empty),
         (S, NoRotation) | (S, Degrees180) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 2, *y - 1, &empty)
+            canvas.filled_rect(x, y - 1, 2, 1, color);
+            canvas.filled_rect(x + 1, y - 2, 2, 1, color);
         }
         (S, Degrees90) | (S, Degrees270) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x - 1, *y - 1, &empty)
+            canvas.filled_rect(x, y - 2, 1, 2, color);
+            canvas.filled_rect(x - 1, y - 3, 1, 2, color);
         }
         (Z, NoRotation) | (Z, Degrees180) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y, &empty)
-                || !canvas.is_empty_or_color(*x - 1, *y - 1, &empty)
+            canvas.filled_rect(x, y - 1, 2, 1, color);
+            canvas.filled_rect(x - 1, y - 2, 2, 1, color);
         }
         (Z, Degrees90) | (Z, Degrees270) => {
-            !canvas.is_empty_or_color(*x, *y, &empty)
-                || !canvas.is_empty_or_color(*x + 1, *y - 1, &empty)
+            canvas.filled_rect(x, y - 2, 1, 2, color);
+            canvas.filled_rect(x + 1, y - 3, 1, 2, color);
         }
     }
+    canvas.set_piece(None);
+}
+
+/// Darken the color towards black (0.0) or keep it as it is (1.0).
+pub fn dim(color: &Color, brightness: f64) -> Color {
+    Color::from_rgb(
+        (color.r as f64 * brightness) as u8,
+        (color.g as f64 * brightness) as u8,
+        (color.b as f64 * brightness) as u8,
+    )
+}
+
+/// Position of a tetromino on a board, relative to its offsets.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Placement {
+    pub shape: Shape,
+    pub rotation: Rotation,
+    pub x: i64,
+    pub y: i64,
 }
 
 pub struct Board {
@@ -322,6 +299,9 @@ pub struct Board {
     x_offset: i64,
     y_offset: i64,
     y_stop: i64,
+    brightness: f64,
+    /// Number of tetrominos, which entered the board so far
+    entered: usize,
 }
 
 impl Board {
@@ -331,16 +311,19 @@ impl Board {
             x_offset,
             y_offset,
             y_stop,
+            brightness: 1.0,
+            entered: 0,
         }
     }
 
-    pub fn add_tetromino(
+    fn push(
         &mut self,
         dx: i64,
         dy: i64,
         color: Color,
         shape: Shape,
         rotation: Rotation,
+        fall: FallState,
     ) {
         self.tetrominos.push(Tetromino {
             x: self.x_offset + dx,
@@ -348,105 +331,138 @@ impl Board {
             color,
             shape,
             rotation,
-            fall: FallState::In,
+            order: self.entered,
+            fall,
+        });
+        self.entered += 1;
+    }
+
+    pub fn add_tetromino(
+        &mut self,
+        dx: i64,
+        dy: i64,
+        color: Color,
+        shape: Shape,
+        rotation: Rotation,
+    ) {
+        self.push(dx, dy, color, shape, rotation, FallState::In);
+    }
+
+    /// Add a tetromino, which is already resting at its final position and
+    /// therefore does not fall in.
+    pub fn place_tetromino(
+        &mut self,
+        dx: i64,
+        dy: i64,
+        color: Color,
+        shape: Shape,
+        rotation: Rotation,
+    ) {
+        self.push(dx, dy, color, shape, rotation, FallState::Hold);
+    }
+
+    pub fn clear(&mut self) {
+        self.tetrominos.clear();
+    }
+
+    /// Dim all tetrominos on the board towards black (0.0) or show them with
+    /// their full color (1.0).
+    pub fn set_brightness(&mut self, brightness: f64) {
+        self.brightness = brightness.clamp(0.0, 1.0);
+    }
+
+    /// Give every tetromino on the board the color picked for its shape.
+    pub fn recolor(&mut self, color: impl Fn(Shape) -> Color) {
+        for tetromino in self.tetrominos.iter_mut() {
+            tetromino.color = color(tetromino.shape);
+        }
+    }
+
+    pub fn placements(&self) -> impl Iterator<Item = Placement> + '_ {
+        self.tetrominos.iter().map(|tetromino| Placement {
+            shape: tetromino.shape,
+            rotation: tetromino.rotation,
+            x: tetromino.x - self.x_offset,
+            y: tetromino.y - self.y_offset,
         })
     }
 
-    pub fn render<C: Canvas>(&self, canvas: &mut C) {
+    pub fn render<C: CellCanvas>(&self, canvas: &mut C) {
+        self.render_painted(canvas, &Painter::default());
+    }
+
+    /// Draw the tetrominos with the colors picked by the given painter.
+    pub fn render_painted<C: CellCanvas>(&self, canvas: &mut C, painter: &Painter) {
         for Tetromino {
             shape,
             rotation,
             x,
             y,
             color,
+            order,
             ..
         } in self.tetrominos.iter()
         {
-            use Rotation::*;
-            use Shape::*;
-            match (shape, rotation) {
-                (L, NoRotation) => {
-                    canvas.filled_rect(*x, *y - 2, 1, 2, color);
-                    canvas.filled_rect(*x + 1, *y - 2, 2, 1, color);
-                }
-                (L, Degrees90) => {
-                    canvas.filled_rect(*x, *y - 3, 1, 3, color);
-                    canvas.filled_rect(*x - 1, *y - 3, 1, 1, color);
-                }
-                (L, Degrees180) => {
-                    canvas.filled_rect(*x, *y - 1, 3, 1, color);
-                    canvas.filled_rect(*x + 2, *y - 2, 1, 1, color);
-                }
-                (L, Degrees270) => {
-                    canvas.filled_rect(*x, *y - 3, 1, 3, color);
-                    canvas.filled_rect(*x + 1, *y - 1, 1, 1, color);
-                }
-                (J, NoRotation) => {
-                    canvas.filled_rect(*x - 2, *y - 2, 2, 1, color);
-                    canvas.filled_rect(*x, *y - 2, 1, 2, color);
-                }
-                (J, Degrees90) => {
-                    canvas.filled_rect(*x, *y - 1, 2, 1, color);
-                    canvas.filled_rect(*x + 1, *y - 3, 1, 2, color);
-                }
-                (J, Degrees180) => {
-                    canvas.filled_rect(*x, *y - 2, 1, 2, color);
-                    canvas.filled_rect(*x + 1, *y - 1, 2, 1, color);
-                }
-                (J, Degrees270) => {
-                    canvas.filled_rect(*x, *y - 3, 1, 3, color);
-                    canvas.filled_rect(*x + 1, *y - 3, 1, 1, color);
-                }
-                (O, _) => {
-                    canvas.filled_rect(*x, *y - 2, 2, 2, color);
-                }
-                (T, NoRotation) => {
-                    canvas.filled_rect(*x - 1, *y - 2, 3, 1, color);
-                    canvas.filled_rect(*x, *y - 1, 1, 1, color);
-                }
-                (T, Degrees90) => {
-                    canvas.filled_rect(*x, *y - 3, 1, 3, color);
-                    canvas.filled_rect(*x - 1, *y - 2, 1, 1, color);
-                }
-                (T, Degrees180) => {
-                    canvas.filled_rect(*x, *y - 1, 3, 1, color);
-                    canvas.filled_rect(*x + 1, *y - 2, 1, 1, color);
-                }
-                (T, Degrees270) => {
-                    canvas.filled_rect(*x, *y - 3, 1, 3, color);
-                    canvas.filled_rect(*x + 1, *y - 2, 1, 1, color);
-                }
-                (I, NoRotation) | (I, Degrees180) => {
-                    canvas.filled_rect(*x, *y - 1, 4, 1, color);
-                }
-                (I, Degrees90) | (I, Degrees270) => {
-                    canvas.filled_rect(*x, *y - 4, 1, 4, color);
-                }
-                (S, NoRotation) | (S, Degrees180) => {
-                    canvas.filled_rect(*x, *y - 1, 2, 1, color);
-                    canvas.filled_rect(*x + 1, *y - 2, 2, 1, color);
-                }
-                (S, Degrees90) | (S, Degrees270) => {
-                    canvas.filled_rect(*x, *y - 2, 1, 2, color);
-                    canvas.filled_rect(*x - 1, *y - 3, 1, 2, color);
-                }
-                (Z, NoRotation) | (Z, Degrees180) => {
-                    canvas.filled_rect(*x, *y - 1, 2, 1, color);
-                    canvas.filled_rect(*x - 1, *y - 2, 2, 1, color);
-                }
-                (Z, Degrees90) | (Z, Degrees270) => {
-                    canvas.filled_rect(*x, *y - 2, 1, 2, color);
-                    canvas.filled_rect(*x + 1, *y - 3, 1, 2, color);
-                }
-            }
+            let color = &dim(&painter.paint(*color, *x, *y, *order), self.brightness);
+            draw_tetromino(canvas, *shape, *rotation, *x, *y, color);
         }
     }
 
-    pub fn update<C: Canvas>(&mut self, canvas: &C) {
-        for tetromino in self.tetrominos.iter_mut() {
-            if tetromino.fall != FallState::Hold
-                && !would_tetromino_collide_with_canvas(tetromino, canvas)
-            {
+    /// Draw a tetromino, which is not part of the board, at a position
+    /// relative to the offsets of the board.
+    pub fn render_placement<C: CellCanvas>(
+        &self,
+        canvas: &mut C,
+        placement: Placement,
+        color: &Color,
+    ) {
+        draw_tetromino(
+            canvas,
+            placement.shape,
+            placement.rotation,
+            self.x_offset + placement.x,
+            self.y_offset + placement.y,
+            color,
+        );
+    }
+
+    /// Whether the tetromino added last is still falling in.
+    pub fn is_last_falling(&self) -> bool {
+        self.tetrominos
+            .last()
+            .is_some_and(|tetromino| tetromino.fall == FallState::In)
+    }
+
+    /// Let all tetrominos, which are not resting, fall down by one row.
+    ///
+    /// A tetromino stops falling, while there is another tetromino of the
+    /// board right below it. Collisions are checked against the positions all
+    /// tetrominos had before the update, which is what the previous frame
+    /// showed. Anything else drawn onto the canvas does not get into the way
+    /// of the tetrominos.
+    ///
+    /// Tetrominos falling out of the board are removed, once they passed the
+    /// given height of the area the board is shown in.
+    pub fn update(&mut self, height: u32) {
+        let occupied: Vec<Vec<(i64, i64)>> = self
+            .tetrominos
+            .iter()
+            .map(|tetromino| tetromino.cells().collect())
+            .collect();
+
+        for (i, tetromino) in self.tetrominos.iter_mut().enumerate() {
+            // Tetrominos falling out of the board pass through anything below
+            // it, like the floor of a well around the digits.
+            let below_board = tetromino.fall == FallState::Out && tetromino.y >= self.y_stop;
+            let blocked = || {
+                tetromino.cells().any(|(x, y)| {
+                    occupied
+                        .iter()
+                        .enumerate()
+                        .any(|(j, cells)| j != i && cells.contains(&(x, y + 1)))
+                })
+            };
+            if tetromino.fall != FallState::Hold && (below_board || !blocked()) {
                 tetromino.y += 1;
             }
 
@@ -456,7 +472,7 @@ impl Board {
         }
 
         self.tetrominos
-            .retain(|tetromino| tetromino.y <= canvas.height() as i64 + 4);
+            .retain(|tetromino| tetromino.y <= height as i64 + 4);
     }
 
     pub fn initiate_fall_out(&mut self) {
@@ -466,6 +482,15 @@ impl Board {
     }
 }
 
+/// Number of updates a digit needs to fade in with [Motion::Fade]
+const FADE_UPDATES: usize = 10;
+
+/// Number of updates between two tetrominos of an animation entering the board
+pub const UPDATES_PER_TETROMINO: usize = 4;
+
+/// Brightness of the ghost showing where a falling tetromino lands
+const GHOST_BRIGHTNESS: f64 = 0.3;
+
 pub struct DigitBoard {
     board: Board,
     digit: Digit,
@@ -474,6 +499,14 @@ pub struct DigitBoard {
     colorscheme: Colorscheme,
     index: usize,
     updates_since_last_anim: usize,
+    motion: Motion,
+    y_stop: i64,
+    font: Rc<Font>,
+    shuffler: Option<Rc<RefCell<Shuffler>>>,
+    ghost: bool,
+    /// Places the tetrominos of the animation come to rest at. Only known,
+    /// while the ghost is shown.
+    landing: Vec<Placement>,
 }
 
 impl DigitBoard {
@@ -482,21 +515,95 @@ impl DigitBoard {
         x_offset: i64,
         y_stop: i64,
         colorscheme: Colorscheme,
+        font: Rc<Font>,
         digit: Digit,
     ) -> Self {
         Self {
             board: Board::new(x_offset, 0, y_stop),
             position,
             digit,
-            animation: digit.into(),
+            animation: font.animation(digit).clone(),
             colorscheme,
             index: 0,
             updates_since_last_anim: 0,
+            motion: Motion::default(),
+            y_stop,
+            font,
+            shuffler: None,
+            ghost: false,
+            landing: vec![],
+        }
+    }
+
+    /// Show a dimmed ghost of the falling tetromino at the place it is going
+    /// to land at.
+    pub fn with_ghost(mut self) -> Self {
+        self.ghost = true;
+        self.landing = settle(&self.animation);
+        self
+    }
+
+    fn change_animation(&mut self, animation: Animation) {
+        self.animation = animation;
+        if self.ghost {
+            self.landing = settle(&self.animation);
+        }
+    }
+
+    /// Vary the animation of every digit shown using the given shuffler.
+    pub fn with_shuffler(mut self, shuffler: Rc<RefCell<Shuffler>>) -> Self {
+        self.shuffler = Some(shuffler);
+        self.change_animation(self.pick_animation(self.digit));
+        self
+    }
+
+    fn pick_animation(&self, digit: Digit) -> Animation {
+        let glyph = self.font.digit_glyph(digit);
+        match &self.shuffler {
+            Some(shuffler) => shuffler.borrow_mut().animation(glyph),
+            None => glyph.tetrominos.clone(),
+        }
+    }
+
+    pub fn with_motion(mut self, motion: Motion) -> Self {
+        self.motion = motion;
+        if motion != Motion::Falling {
+            self.assemble();
+        }
+        self
+    }
+
+    /// Replace the contents of the board with the fully assembled digit.
+    fn assemble(&mut self) {
+        self.board.clear();
+        for Placement {
+            shape,
+            rotation,
+            x,
+            y,
+        } in settle(&self.animation)
+        {
+            let color = self.colorscheme.apply(shape, self.digit, self.position);
+            self.board
+                .place_tetromino(x, self.y_stop + y, color, shape, rotation);
+        }
+        self.index = self.animation.len();
+        self.updates_since_last_anim = 0;
+
+        if self.motion == Motion::Fade {
+            self.board.set_brightness(0.0);
         }
     }
 
-    pub fn update<C: Canvas>(&mut self, canvas: &C) {
-        if self.index < self.animation.len() && self.updates_since_last_anim > 3 {
+    pub fn update(&mut self, height: u32) {
+        if self.motion == Motion::Fade && self.updates_since_last_anim <= FADE_UPDATES {
+            self.board
+                .set_brightness(self.updates_since_last_anim as f64 / FADE_UPDATES as f64);
+        }
+
+        if self.index < self.animation.len()
+            && self.updates_since_last_anim >= UPDATES_PER_TETROMINO
+        {
             let FallingTetromino {
                 shape,
                 rotation,
@@ -510,19 +617,219 @@ impl DigitBoard {
             self.updates_since_last_anim = 0;
         }
 
-        self.board.update(canvas);
+        self.board.update(height);
         self.updates_since_last_anim += 1;
     }
 
-    pub fn render<C: Canvas>(&self, canvas: &mut C) {
-        self.board.render(canvas);
+    pub fn render<C: CellCanvas>(&self, canvas: &mut C, painter: &Painter) {
+        // The landing place is taken from the simulation of the whole
+        // animation, as tetrominos below the falling one may still be
+        // falling themselves.
+        if self.ghost && self.index > 0 && self.board.is_last_falling() {
+            if let Some(&placement) = self.landing.get(self.index - 1) {
+                let placement = Placement {
+                    y: self.y_stop + placement.y,
+                    ..placement
+                };
+                let color = painter.paint(
+                    self.colorscheme
+                        .apply(placement.shape, self.digit, self.position),
+                    self.board.x_offset + placement.x,
+                    self.board.y_offset + placement.y,
+                    self.board.entered - 1,
+                );
+                self.board
+                    .render_placement(canvas, placement, &dim(&color, GHOST_BRIGHTNESS));
+            }
+        }
+
+        self.board.render_painted(canvas, painter);
+    }
+
+    /// The tetromino of the animation entering the board next, together with
+    /// its color. There is none, once the digit is complete.
+    pub fn next_tetromino(&self) -> Option<(FallingTetromino, Color)> {
+        if self.motion != Motion::Falling {
+            return None;
+        }
+        self.animation.get(self.index).map(|&tetromino| {
+            let color = self
+                .colorscheme
+                .apply(tetromino.shape, self.digit, self.position);
+            (tetromino, color)
+        })
+    }
+
+    /// Color tetrominos entering the board from now on with the given
+    /// colorscheme. Tetrominos already on the board keep their colors.
+    pub fn set_colorscheme(&mut self, colorscheme: Colorscheme) {
+        self.colorscheme = colorscheme;
+    }
+
+    /// Switch to the given colorscheme right away, including the tetrominos
+    /// already on the board.
+    pub fn recolor(&mut self, colorscheme: Colorscheme) {
+        self.board
+            .recolor(|shape| colorscheme.apply(shape, self.digit, self.position));
+        self.colorscheme = colorscheme;
+    }
+
+    /// Replace the animation of the board and show the digit it assembles
+    /// right away.
+    pub fn set_animation(&mut self, animation: Animation) {
+        self.change_animation(animation);
+        self.assemble();
+    }
+
+    /// Append a tetromino to the animation. It enters the board as soon as
+    /// all tetrominos before it did.
+    pub fn push_tetromino(&mut self, tetromino: FallingTetromino) {
+        let mut animation = std::mem::take(&mut self.animation);
+        animation.push(tetromino);
+        self.change_animation(animation);
+        if self.motion != Motion::Falling {
+            self.assemble();
+        }
+    }
+
+    /// Let all tetrominos of the animation fall in again, starting with an
+    /// empty board.
+    pub fn replay(&mut self) {
+        self.board.clear();
+        self.board.set_brightness(1.0);
+        self.index = 0;
+        self.updates_since_last_anim = UPDATES_PER_TETROMINO;
     }
 
     pub fn set_digit(&mut self, digit: Digit) {
-        self.board.initiate_fall_out();
         self.digit = digit;
-        self.animation = digit.into();
+        let animation = self.pick_animation(digit);
+        self.change_animation(animation);
+        if self.motion != Motion::Falling {
+            self.assemble();
+            return;
+        }
+
+        self.board.initiate_fall_out();
         self.index = 0;
         self.updates_since_last_anim = 0;
     }
 }
+
+/// Brightness of pulsing separators at the dimmest point of a second
+const PULSE_MIN_BRIGHTNESS: f64 = 0.3;
+
+/// The two dots separating hours, minutes and seconds.
+pub struct SeparatorBoard {
+    dots: Vec<Board>,
+    color: Color,
+    style: SeparatorStyle,
+    motion: Motion,
+    visible: bool,
+}
+
+impl SeparatorBoard {
+    /// Create the separator for digits of the given height. The lower dot
+    /// rests at a fifth of the height, the upper one at three fifths.
+    pub fn new(
+        x_offset: i64,
+        y_stop: i64,
+        digit_height: i64,
+        color: Color,
+        style: SeparatorStyle,
+        motion: Motion,
+    ) -> Self {
+        // The upper dot starts a bit higher, so that it enters after the
+        // lower one.
+        let dots = [
+            (0, y_stop - digit_height / 5),
+            (-4, y_stop - 3 * digit_height / 5),
+        ]
+        .iter()
+        .map(|&(y_offset, y_stop)| Board::new(x_offset, y_offset, y_stop))
+        .collect();
+
+        let mut separator = Self {
+            dots,
+            color,
+            style,
+            motion,
+            visible: true,
+        };
+        separator.drop_in();
+        separator
+    }
+
+    fn drop_in(&mut self) {
+        for dot in self.dots.iter_mut() {
+            if self.motion == Motion::Falling {
+                dot.add_tetromino(0, 0, self.color, Shape::O, Rotation::NoRotation);
+            } else {
+                dot.place_tetromino(
+                    0,
+                    dot.y_stop - dot.y_offset,
+                    self.color,
+                    Shape::O,
+                    Rotation::NoRotation,
+                );
+            }
+        }
+    }
+
+    /// Color the dots with the given color, once they drop in the next time.
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    /// Color the dots with the given color right away.
+    pub fn recolor(&mut self, color: Color) {
+        self.color = color;
+        for dot in self.dots.iter_mut() {
+            dot.recolor(|_| color);
+        }
+    }
+
+    /// Let the dots fall out and drop in again, if the style asks for it.
+    pub fn redrop(&mut self) {
+        if self.style != SeparatorStyle::Redrop {
+            return;
+        }
+
+        for dot in self.dots.iter_mut() {
+            if self.motion == Motion::Falling {
+                dot.initiate_fall_out();
+            } else {
+                dot.clear();
+            }
+        }
+        self.drop_in();
+    }
+
+    /// Advance the separator. `second` is the fraction of the second shown
+    /// right now, which passed so far. Separators, which are not `running`, stay still.
+    pub fn update(&mut self, height: u32, second: f64, running: bool) {
+        self.visible = !running || self.style != SeparatorStyle::Blink || second < 0.5;
+
+        let brightness = if running && self.style == SeparatorStyle::Pulse {
+            let wave = 0.5 + 0.5 * (second * std::f64::consts::TAU).cos();
+            PULSE_MIN_BRIGHTNESS + (1.0 - PULSE_MIN_BRIGHTNESS) * wave
+        } else {
+            1.0
+        };
+
+        for dot in self.dots.iter_mut() {
+            dot.set_brightness(brightness);
+            dot.update(height);
+        }
+    }
+
+    pub fn render<C: CellCanvas>(&self, canvas: &mut C, painter: &Painter) {
+        if !self.visible {
+            return;
+        }
+
+        for dot in self.dots.iter() {
+            dot.render_painted(canvas, painter);
+        }
+    }
+}
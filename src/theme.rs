@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use pixel_loop::color::Color;
+
+use crate::background::parse_color;
+use crate::colorscheme::CustomColorscheme;
+
+/// Colors of a terminal theme
+#[derive(Debug, Default)]
+struct Palette {
+    /// The 16 ANSI colors: black, red, green, yellow, blue, magenta, cyan,
+    /// white and their bright variants
+    ansi: [Option<Color>; 16],
+    /// Some themes have a dedicated orange
+    orange: Option<Color>,
+    background: Option<Color>,
+}
+
+impl Palette {
+    fn ansi(&self, index: usize) -> Result<Color> {
+        self.ansi[index]
+            .or(self.ansi[index + 8])
+            .ok_or_else(|| anyhow!("The theme does not define ANSI color {}", index))
+    }
+
+    /// Colors for the shapes in the order of `Shape::ALL`, matching the
+    /// original Tetris colors as far as possible: L orange, J blue, O yellow,
+    /// T magenta, I cyan, S green and Z red.
+    fn shape_colors(&self) -> Result<[Color; 7]> {
+        let red = self.ansi(1)?;
+        let yellow = self.ansi(3)?;
+        let orange = self.orange.unwrap_or_else(|| {
+            let mix = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+            Color::from_rgb(
+                mix(red.r, yellow.r),
+                mix(red.g, yellow.g),
+                mix(red.b, yellow.b),
+            )
+        });
+        Ok([
+            orange,
+            self.ansi(4)?,
+            yellow,
+            self.ansi(5)?,
+            self.ansi(6)?,
+            self.ansi(2)?,
+            red,
+        ])
+    }
+}
+
+/// Create a colorscheme from the palette of a terminal theme, to let the clock
+/// match the terminal. The format is told by the extension of the file:
+/// base16 schemes (`.yaml`, `.yml`), Alacritty themes (`.toml`), iTerm2
+/// color presets (`.itermcolors`) and otherwise X resources.
+pub fn import(path: &Path) -> Result<CustomColorscheme> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read theme {}", path.display()))?;
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+
+    let error = || format!("Could not import theme {}", path.display());
+    let palette = match extension.as_deref() {
+        Some("yaml" | "yml") => parse_base16(&content),
+        Some("toml") => parse_alacritty(&content),
+        Some("itermcolors") => parse_iterm(&content),
+        _ => parse_xresources(&content),
+    }
+    .with_context(error)?;
+    let colors = palette.shape_colors().with_context(error)?;
+
+    let background = palette
+        .background
+        .map(|color| format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b));
+    Ok(CustomColorscheme::from_shapes(colors, background))
+}
+
+/// Parse colors written like `#1d1f21`, `0x1d1f21` or `rgb:1d/1f/21`.
+fn parse_theme_color(color: &str) -> Result<Color> {
+    let color = color.trim().trim_matches(['"', '\'']);
+    if let Some(channels) = color.strip_prefix("rgb:") {
+        // Channels of X colors may have one to four hexadecimal digits.
+        let channels: Vec<&str> = channels.split('/').collect();
+        if let [r, g, b] = channels[..] {
+            let channel = |channel: &str| -> Result<u8> {
+                if !(1..=4).contains(&channel.len())
+                    || !channel.chars().all(|digit| digit.is_ascii_hexdigit())
+                {
+                    return Err(anyhow!("'{}' is not a channel of 1 to 4 digits", channel));
+                }
+                let value = u32::from_str_radix(channel, 16)?;
+                let max = 16u32.pow(channel.len() as u32) - 1;
+                Ok((value * 255 / max.max(1)) as u8)
+            };
+            if let (Ok(r), Ok(g), Ok(b)) = (channel(r), channel(g), channel(b)) {
+                return Ok(Color::from_rgb(r, g, b));
+            }
+        }
+        return Err(anyhow!("'{}' is not a color like rgb:1d/1f/21", color));
+    }
+    parse_color(color.strip_prefix("0x").unwrap_or(color))
+}
+
+/// base16 schemes name their colors `base00` to `base0F`, either at the top
+/// level or below `palette`.
+fn parse_base16(content: &str) -> Result<Palette> {
+    let mut base = HashMap::new();
+    for line in content.lines().filter(|line| !line.trim().starts_with('#')) {
+        let line = line.split(" #").next().unwrap_or_default();
+        if let Some((key, value)) = line.split_once(':') {
+            let key = key.trim().trim_matches(['"', '\'']).to_lowercase();
+            if key.len() == 6 && key.starts_with("base0") {
+                let color = parse_theme_color(value).with_context(|| key.clone())?;
+                base.insert(key, color);
+            }
+        }
+    }
+    if base.is_empty() {
+        return Err(anyhow!("No base16 colors (base00 to base0F) found"));
+    }
+
+    let color = |key: &str| base.get(key).copied();
+    let mut palette = Palette {
+        orange: color("base09"),
+        background: color("base00"),
+        ..Palette::default()
+    };
+    // Mapping of the colors onto the ANSI colors, as done by base16 shell
+    for (index, key) in [
+        "base00", "base08", "base0b", "base0a", "base0d", "base0e", "base0c", "base05",
+    ]
+    .iter()
+    .enumerate()
+    {
+        palette.ansi[index] = color(key);
+    }
+    Ok(palette)
+}
+
+/// Alacritty themes have tables `colors.primary`, `colors.normal` and
+/// `colors.bright`.
+fn parse_alacritty(content: &str) -> Result<Palette> {
+    let theme: toml::Table = toml::from_str(content)?;
+    let colors = theme
+        .get("colors")
+        .and_then(|colors| colors.as_table())
+        .ok_or_else(|| anyhow!("No colors table found"))?;
+    let color = |table: &str, name: &str| -> Result<Option<Color>> {
+        match colors.get(table).and_then(|table| table.get(name)) {
+            Some(toml::Value::String(color)) => parse_theme_color(color)
+                .with_context(|| format!("colors.{}.{}", table, name))
+                .map(Some),
+            _ => Ok(None),
+        }
+    };
+
+    let mut palette = Palette {
+        background: color("primary", "background")?,
+        ..Palette::default()
+    };
+    let names = [
+        "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    ];
+    for (index, name) in names.iter().enumerate() {
+        palette.ansi[index] = color("normal", name)?;
+        palette.ansi[index + 8] = color("bright", name)?;
+    }
+    Ok(palette)
+}
+
+/// X resources like `*.color1: #cc0000` or `URxvt.background: #1d1f21`,
+/// including colors defined with `#define`.
+fn parse_xresources(content: &str) -> Result<Palette> {
+    let mut defines = HashMap::new();
+    let mut palette = Palette::default();
+    for line in content.lines().map(str::trim) {
+        if let Some(define) = line.strip_prefix("#define") {
+            if let Some((name, value)) = define.trim().split_once(char::is_whitespace) {
+                defines.insert(name.to_string(), value.trim().to_string());
+            }
+            continue;
+        }
+        if line.starts_with('!') {
+            continue;
+        }
+        let Some((resource, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        let value = defines.get(value).map_or(value, String::as_str);
+        // Only the last part of the resource names the color.
+        let name = resource
+            .rsplit(['.', '*'])
+            .next()
+            .unwrap_or_default()
+            .trim();
+        let color = || parse_theme_color(value).with_context(|| resource.trim().to_string());
+        if name == "background" {
+            palette.background = Some(color()?);
+        } else if let Some(index) = name.strip_prefix("color") {
+            if let Ok(index) = index.parse::<usize>() {
+                if index < 16 {
+                    palette.ansi[index] = Some(color()?);
+                }
+            }
+        }
+    }
+    Ok(palette)
+}
+
+/// iTerm2 color presets are property lists with a dictionary per color, like
+/// `Ansi 1 Color` or `Background Color`, holding its components from 0 to 1.
+fn parse_iterm(content: &str) -> Result<Palette> {
+    let mut palette = Palette::default();
+    let mut rest = content;
+    while let Some(start) = rest.find("<key>") {
+        rest = &rest[start + "<key>".len()..];
+        let Some(end) = rest.find("</key>") else {
+            break;
+        };
+        let key = rest[..end].trim().to_string();
+        rest = &rest[end..];
+
+        // Only keys followed by a dictionary describe colors.
+        let after = rest["</key>".len()..].trim_start();
+        if !after.starts_with("<dict>") {
+            continue;
+        }
+        let dict_end = after
+            .find("</dict>")
+            .ok_or_else(|| anyhow!("Unclosed dictionary of '{}'", key))?;
+        let color = parse_iterm_color(&after[..dict_end]).with_context(|| key.clone())?;
+
+        if key == "Background Color" {
+            palette.background = Some(color);
+        } else if let Some(index) = key
+            .strip_prefix("Ansi ")
+            .and_then(|key| key.strip_suffix(" Color"))
+            .and_then(|index| index.parse::<usize>().ok())
+        {
+            if index < 16 {
+                palette.ansi[index] = Some(color);
+            }
+        }
+    }
+    Ok(palette)
+}
+
+fn parse_iterm_color(dict: &str) -> Result<Color> {
+    let component = |name: &str| -> Result<u8> {
+        let key = format!("<key>{} Component</key>", name);
+        let value = dict
+            .find(&key)
+            .map(|start| &dict[start + key.len()..])
+            .and_then(|rest| rest.trim_start().strip_prefix("<real>"))
+            .and_then(|rest| rest.split_once("</real>"))
+            .and_then(|(value, _)| value.trim().parse::<f64>().ok())
+            .ok_or_else(|| anyhow!("No {} component", name.to_lowercase()))?;
+        Ok((value.clamp(0.0, 1.0) * 255.0).round() as u8)
+    };
+    Ok(Color::from_rgb(
+        component("Red")?,
+        component("Green")?,
+        component("Blue")?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Option<Color> {
+        Some(Color::from_rgb(r, g, b))
+    }
+
+    #[test]
+    fn parses_x_color_channels() {
+        let color = parse_theme_color("rgb:f/80/ffff").unwrap();
+        assert_eq!(Some(color), rgb(255, 128, 255));
+        assert_eq!(parse_theme_color("0x1d1f21").ok(), rgb(0x1d, 0x1f, 0x21));
+
+        for color in [
+            "rgb:fffff/0/0",
+            "rgb:123456789/0/0",
+            "rgb:/0/0",
+            "rgb:+f/0/0",
+        ] {
+            assert!(parse_theme_color(color).is_err(), "{}", color);
+        }
+    }
+
+    #[test]
+    fn parses_base16_schemes() {
+        let palette = parse_base16(
+            "scheme: \"Test\"\n\
+             base00: \"1d1f21\" # background\n\
+             base08: \"cc6666\"\n\
+             base09: \"de935f\"\n\
+             base0D: \"81a2be\"\n",
+        )
+        .unwrap();
+        assert_eq!(palette.background, rgb(0x1d, 0x1f, 0x21));
+        assert_eq!(palette.orange, rgb(0xde, 0x93, 0x5f));
+        assert_eq!(palette.ansi[1], rgb(0xcc, 0x66, 0x66));
+        assert_eq!(palette.ansi[4], rgb(0x81, 0xa2, 0xbe));
+
+        assert!(parse_base16("scheme: \"Empty\"").is_err());
+    }
+
+    #[test]
+    fn parses_alacritty_themes() {
+        let palette = parse_alacritty(
+            "[colors.primary]\n\
+             background = '#282828'\n\
+             [colors.normal]\n\
+             red = '0xcc241d'\n\
+             [colors.bright]\n\
+             red = '#fb4934'\n\
+             green = '#b8bb26'\n",
+        )
+        .unwrap();
+        assert_eq!(palette.background, rgb(0x28, 0x28, 0x28));
+        assert_eq!(palette.ansi[1], rgb(0xcc, 0x24, 0x1d));
+        assert_eq!(palette.ansi[9], rgb(0xfb, 0x49, 0x34));
+        // Colors missing from the normal ones fall back to the bright ones.
+        assert_eq!(palette.ansi(2).ok(), rgb(0xb8, 0xbb, 0x26));
+    }
+
+    #[test]
+    fn parses_xresources() {
+        let palette = parse_xresources(
+            "! comment\n\
+             #define red #cc0000\n\
+             *.background: rgb:1d/1f/21\n\
+             *.color1: red\n\
+             URxvt.color12: #729fcf\n",
+        )
+        .unwrap();
+        assert_eq!(palette.background, rgb(0x1d, 0x1f, 0x21));
+        assert_eq!(palette.ansi[1], rgb(0xcc, 0, 0));
+        assert_eq!(palette.ansi[12], rgb(0x72, 0x9f, 0xcf));
+    }
+
+    #[test]
+    fn parses_iterm_presets() {
+        let palette = parse_iterm(
+            "<plist><dict>\n\
+             <key>Ansi 1 Color</key>\n\
+             <dict>\n\
+             <key>Blue Component</key><real>0.0</real>\n\
+             <key>Green Component</key><real>0.5</real>\n\
+             <key>Red Component</key><real>1</real>\n\
+             </dict>\n\
+             <key>Background Color</key>\n\
+             <dict>\n\
+             <key>Blue Component</key><real>0.2</real>\n\
+             <key>Green Component</key><real>0.2</real>\n\
+             <key>Red Component</key><real>0.2</real>\n\
+             </dict>\n\
+             </dict></plist>\n",
+        )
+        .unwrap();
+        assert_eq!(palette.ansi[1], rgb(255, 128, 0));
+        assert_eq!(palette.background, rgb(51, 51, 51));
+    }
+}
@@ -0,0 +1,116 @@
+use std::rc::Rc;
+
+use chrono::Duration;
+use pixel_loop::color::Color;
+
+use crate::colorscheme::{Colorscheme, CustomColorscheme};
+
+/// How close a countdown is to running out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Normal,
+    Amber,
+    Red,
+}
+
+/// Colors of a countdown running out, to be noticed from the corner of the
+/// eye: the colorscheme stays as it is above the first threshold, turns
+/// amber below it and red below the second one.
+pub struct Urgency {
+    amber_at: Duration,
+    red_at: Duration,
+    level: Level,
+}
+
+impl Urgency {
+    pub fn new(amber_at: Duration, red_at: Duration) -> Self {
+        Self {
+            amber_at,
+            red_at,
+            level: Level::Normal,
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Advance to the level of the remaining time. Returns the colorscheme to
+    /// show, once the countdown turns amber or red.
+    pub fn update(&mut self, remaining: Duration) -> Option<Colorscheme> {
+        let level = if remaining < self.red_at {
+            Level::Red
+        } else if remaining < self.amber_at {
+            Level::Amber
+        } else {
+            Level::Normal
+        };
+        if level == self.level {
+            return None;
+        }
+
+        self.level = level;
+        match level {
+            Level::Normal => None,
+            Level::Amber => Some(shades([
+                (255, 176, 0),
+                (230, 140, 0),
+                (255, 200, 60),
+                (200, 120, 0),
+                (255, 190, 40),
+                (240, 160, 20),
+                (215, 130, 10),
+            ])),
+            Level::Red => Some(shades([
+                (255, 40, 40),
+                (200, 0, 0),
+                (255, 90, 80),
+                (170, 0, 20),
+                (255, 60, 60),
+                (230, 30, 30),
+                (190, 20, 20),
+            ])),
+        }
+    }
+}
+
+/// Colorscheme with the given colors for the shapes, in the order of
+/// `Shape::ALL`.
+fn shades(colors: [(u8, u8, u8); 7]) -> Colorscheme {
+    let colors = colors.map(|(r, g, b)| Color::from_rgb(r, g, b));
+    Colorscheme::Custom(Rc::new(CustomColorscheme::from_shapes(colors, None)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::Digit;
+    use crate::tetromino::Shape;
+
+    #[test]
+    fn turns_amber_and_then_red() {
+        let mut urgency = Urgency::new(Duration::minutes(5), Duration::minutes(1));
+        assert!(urgency.update(Duration::minutes(10)).is_none());
+        assert_eq!(urgency.level(), Level::Normal);
+
+        let amber = urgency.update(Duration::minutes(4)).unwrap();
+        assert_eq!(urgency.level(), Level::Amber);
+        let color = amber.apply(Shape::L, Digit::Zero, 0);
+        assert_eq!(color, Color::from_rgb(255, 176, 0));
+        // The colorscheme is only returned, once the level changes.
+        assert!(urgency.update(Duration::minutes(3)).is_none());
+
+        let red = urgency.update(Duration::seconds(59)).unwrap();
+        assert_eq!(urgency.level(), Level::Red);
+        let color = red.apply(Shape::L, Digit::Zero, 0);
+        assert_eq!(color, Color::from_rgb(255, 40, 40));
+        assert!(urgency.update(Duration::zero()).is_none());
+    }
+
+    #[test]
+    fn skips_amber_when_running_out_at_once() {
+        let mut urgency = Urgency::new(Duration::minutes(5), Duration::minutes(1));
+        assert!(urgency.update(Duration::seconds(30)).is_some());
+        assert_eq!(urgency.level(), Level::Red);
+    }
+}